@@ -0,0 +1,62 @@
+//! Plays arbitrary moves on different implementations, checking that they agree
+//! and that their counters match their cells.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use mine_sweeperr::{
+    zobrist_key, CellContent, CellState, Difficulty, MSHash, MSMatrix, MineSweeper, Move,
+};
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    height: u8,
+    width: u8,
+    mines: u8,
+    seed: u64,
+    moves: Vec<Move>,
+}
+
+fn check(ms: &impl MineSweeper) {
+    let cells = ms.cells_snapshot();
+    let count = |state| cells.iter().filter(|cell| cell.state == state).count();
+    let exploded = cells
+        .iter()
+        .filter(|cell| cell.state == CellState::Open && cell.content == CellContent::Mine)
+        .count();
+    let state = ms.get_game_state();
+    assert_eq!(state.opened, count(CellState::Open));
+    assert_eq!(state.flagged, count(CellState::Flagged));
+    assert_eq!(state.exploded, exploded);
+    assert_eq!(
+        state.mines_left,
+        ms.mines()
+            .saturating_sub(state.flagged)
+            .saturating_sub(exploded)
+    );
+    let hash = cells.iter().enumerate().fold(0, |hash, (i, &cell)| {
+        hash ^ zobrist_key((i / ms.width(), i % ms.width()), cell)
+    });
+    assert_eq!(ms.position_hash(), hash);
+}
+
+fuzz_target!(|input: Input| {
+    let difficulty = Difficulty::custom(
+        input.height as usize % 32,
+        input.width as usize % 32,
+        input.mines as usize,
+    );
+    let (Ok(mut matrix), Ok(mut hash)) = (
+        <MSMatrix as MineSweeper>::from_seed(difficulty, (0, 0), input.seed),
+        MSHash::from_seed(difficulty, (0, 0), input.seed),
+    ) else {
+        return;
+    };
+    for action in input.moves {
+        assert_eq!(action.play(&mut matrix), action.play(&mut hash));
+        assert_eq!(matrix.cells_snapshot(), hash.cells_snapshot());
+        check(&matrix);
+        check(&hash);
+    }
+});
@@ -0,0 +1,25 @@
+//! Parses arbitrary saves and checks that they survive a round trip.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use mine_sweeperr::{BoardView, MSMatrix, SavedGame};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(save) = serde_json::from_slice::<SavedGame>(data) else {
+        return;
+    };
+    let json = serde_json::to_string(&save).unwrap();
+    assert_eq!(serde_json::from_str::<SavedGame>(&json).unwrap(), save);
+    assert_eq!(serde_json::from_str::<SavedGame>(&json).unwrap().redacted(), save.redacted());
+
+    // keeps the boards small, restoring must never panic
+    let (height, width, _) = save.difficulty.into();
+    if height.checked_mul(width).is_some_and(|cells| cells <= 1024) {
+        if let Ok(ms) = save.restore::<MSMatrix>() {
+            let view = BoardView::new(&ms);
+            let json = serde_json::to_string(&view).unwrap();
+            assert_eq!(serde_json::from_str::<BoardView>(&json).unwrap(), view);
+        }
+    }
+});
@@ -0,0 +1,25 @@
+//! Loads arbitrary files with a `FileStorage` and checks that the games survive a round trip.
+
+#![no_main]
+
+use std::{env, fs, path::PathBuf, process, sync::OnceLock};
+
+use libfuzzer_sys::fuzz_target;
+use mine_sweeperr::{FileStorage, Storage};
+
+/// The directory of the storage, one for each fuzzing process.
+fn dir() -> &'static PathBuf {
+    static DIR: OnceLock<PathBuf> = OnceLock::new();
+    DIR.get_or_init(|| env::temp_dir().join(format!("mine_sweeperr-fuzz-{}", process::id())))
+}
+
+fuzz_target!(|data: &[u8]| {
+    let mut storage = FileStorage::new(dir()).unwrap();
+    fs::write(dir().join("game.msave"), data).unwrap();
+    // corrupted files must be refused without panicking
+    let Ok(Some(save)) = storage.load("game") else {
+        return;
+    };
+    storage.save("game", &save).unwrap();
+    assert_eq!(storage.load("game").unwrap(), Some(save));
+});
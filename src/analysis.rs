@@ -0,0 +1,185 @@
+//! Structural analysis of boards.
+
+use std::collections::VecDeque;
+
+use crate::{neighbors, BoardView, CellContent, CellView, Coordinate, MineSweeper};
+
+/// A reflection or a rotation of the board.
+/// Those swapping rows and columns only apply to square boards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Symmetry {
+    /// Reflection swapping the top and the bottom.
+    Horizontal,
+    /// Reflection swapping the left and the right.
+    Vertical,
+    /// Reflection across the diagonal from the top left corner, swapping rows and columns.
+    Diagonal,
+    /// Reflection across the diagonal from the top right corner.
+    AntiDiagonal,
+    /// Rotation by 90 degrees clockwise.
+    Rotate90,
+    Rotate180,
+    /// Rotation by 270 degrees clockwise.
+    Rotate270,
+}
+
+impl Symmetry {
+    /// All the symmetries, in the order they are reported by [`symmetries`].
+    pub const ALL: [Symmetry; 7] = [
+        Symmetry::Horizontal,
+        Symmetry::Vertical,
+        Symmetry::Diagonal,
+        Symmetry::AntiDiagonal,
+        Symmetry::Rotate90,
+        Symmetry::Rotate180,
+        Symmetry::Rotate270,
+    ];
+
+    /// Returns `true` if the symmetry maps a board with the given size onto itself.
+    pub fn applies_to(self, height: usize, width: usize) -> bool {
+        height == width
+            || matches!(
+                self,
+                Symmetry::Horizontal | Symmetry::Vertical | Symmetry::Rotate180
+            )
+    }
+
+    /// Returns where the given cell goes when applying the symmetry.
+    /// The symmetry must [apply](Self::applies_to) to the size of the board.
+    pub fn apply(self, (r, c): Coordinate, height: usize, width: usize) -> Coordinate {
+        let (last_r, last_c) = (height - 1, width - 1);
+        match self {
+            Symmetry::Horizontal => (last_r - r, c),
+            Symmetry::Vertical => (r, last_c - c),
+            Symmetry::Diagonal => (c, r),
+            Symmetry::AntiDiagonal => (last_c - c, last_r - r),
+            Symmetry::Rotate90 => (c, last_r - r),
+            Symmetry::Rotate180 => (last_r - r, last_c - c),
+            Symmetry::Rotate270 => (last_c - c, r),
+        }
+    }
+}
+
+/// Returns the symmetries that leave the position of the mines unchanged.
+///
+/// This reads the position of all the mines, including the closed ones.
+pub fn symmetries(ms: &impl MineSweeper) -> Vec<Symmetry> {
+    let (height, width) = (ms.height(), ms.width());
+    let is_mine: Vec<_> = ms
+        .cells_snapshot()
+        .iter()
+        .map(|cell| cell.content == CellContent::Mine)
+        .collect();
+    Symmetry::ALL
+        .into_iter()
+        .filter(|symmetry| symmetry.applies_to(height, width))
+        .filter(|symmetry| {
+            (0..height * width).all(|i| {
+                let (r, c) = symmetry.apply((i / width, i % width), height, width);
+                is_mine[i] == is_mine[r * width + c]
+            })
+        })
+        .collect()
+}
+
+/// The kind of a [`Component`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ComponentKind {
+    /// Open cells with no mines around them, opened together by a single click.
+    Opening,
+    /// Open numbers next to at least one closed cell.
+    Border,
+    /// Closed cells, flagged or not.
+    Closed,
+}
+
+/// A group of cells of the same kind, each one next to another one of the group.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Component {
+    pub kind: ComponentKind,
+    /// The cells of the component, in row-major order.
+    pub cells: Vec<Coordinate>,
+}
+
+/// Splits what a player can see into [openings](ComponentKind::Opening),
+/// [borders](ComponentKind::Border) and [closed regions](ComponentKind::Closed).
+///
+/// Numbers with no closed neighbors and opened mines don't belong to any component.
+/// Components are sorted by their first cell.
+pub fn components(view: &BoardView) -> Vec<Component> {
+    let (height, width) = (view.height, view.width);
+    let is_closed = |i: usize| matches!(view.cells[i], CellView::Closed | CellView::Flagged);
+    let kinds: Vec<_> = (0..view.cells.len())
+        .map(|i| match view.cells[i] {
+            CellView::Closed | CellView::Flagged => Some(ComponentKind::Closed),
+            CellView::Number(0) => Some(ComponentKind::Opening),
+            CellView::Number(_)
+                if neighbors((i / width, i % width), height, width)
+                    .any(|(r, c)| is_closed(r * width + c)) =>
+            {
+                Some(ComponentKind::Border)
+            }
+            _ => None,
+        })
+        .collect();
+    let mut seen = vec![false; kinds.len()];
+    let mut result = Vec::new();
+    for start in 0..kinds.len() {
+        let Some(kind) = kinds[start] else {
+            continue;
+        };
+        if seen[start] {
+            continue;
+        }
+        seen[start] = true;
+        let mut cells = Vec::new();
+        let mut queue = VecDeque::from([(start / width, start % width)]);
+        while let Some(coord) = queue.pop_front() {
+            cells.push(coord);
+            for neighbor @ (r, c) in neighbors(coord, height, width) {
+                let i = r * width + c;
+                if !seen[i] && kinds[i] == Some(kind) {
+                    seen[i] = true;
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+        cells.sort_unstable();
+        result.push(Component { kind, cells });
+    }
+    result
+}
+
+/// Returns the fraction of mines in each square of `window`×`window` cells,
+/// indexed by the row and the column of its top left cell.
+///
+/// The result has `height - window + 1` rows and `width - window + 1` columns,
+/// and is empty if `window` is `0` or bigger than the board.
+/// Like [`symmetries`], this reads the position of all the mines.
+pub fn density_heatmap(ms: &impl MineSweeper, window: usize) -> Vec<Vec<f32>> {
+    let (height, width) = (ms.height(), ms.width());
+    if window == 0 || window > height || window > width {
+        return Vec::new();
+    }
+    // sums[r][c] is the number of mines in the rows before r and the columns before c
+    let mut sums = vec![vec![0; width + 1]; height + 1];
+    let cells = ms.cells_snapshot();
+    for r in 0..height {
+        for c in 0..width {
+            let mine = (cells[r * width + c].content == CellContent::Mine) as usize;
+            sums[r + 1][c + 1] = sums[r][c + 1] + sums[r + 1][c] - sums[r][c] + mine;
+        }
+    }
+    let area = (window * window) as f32;
+    (0..=height - window)
+        .map(|r| {
+            (0..=width - window)
+                .map(|c| {
+                    let (bottom, right) = (r + window, c + window);
+                    let mines = sums[bottom][right] + sums[r][c] - sums[r][right] - sums[bottom][c];
+                    mines as f32 / area
+                })
+                .collect()
+        })
+        .collect()
+}
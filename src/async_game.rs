@@ -0,0 +1,130 @@
+use std::{future::Future, sync::Arc};
+
+use tokio::sync::{broadcast, Mutex, MutexGuard};
+
+use crate::{Cell, CellState, Coordinate, Game, GameState, MineSweeper, Move, OpenResult, Result};
+
+/// The number of events kept for the subscribers of an [`AsyncGame`] that lag behind.
+const EVENTS_CAPACITY: usize = 64;
+
+/// A move made on an [`AsyncGame`], sent to its [subscribers](AsyncGame::subscribe).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MoveEvent {
+    pub action: Move,
+    /// The number of moves made so far, including this one.
+    pub moves: u64,
+    /// The state of the game after the move.
+    pub state: GameState,
+}
+
+/// A handle to a [`Game`] shared between async tasks, available with the `tokio` feature.
+///
+/// Cloning the handle gives access to the same game.
+/// Moves and queries lock the game one at a time: the game enforces its [rules](crate::GameRules)
+/// and calls its hooks as usual, and every successful move is broadcast to the [subscribers](AsyncGame::subscribe).
+pub struct AsyncGame<M: MineSweeper> {
+    game: Arc<Mutex<Game<M>>>,
+    events: broadcast::Sender<MoveEvent>,
+}
+
+impl<M: MineSweeper> Clone for AsyncGame<M> {
+    fn clone(&self) -> Self {
+        AsyncGame {
+            game: Arc::clone(&self.game),
+            events: self.events.clone(),
+        }
+    }
+}
+
+impl<M: MineSweeper> AsyncGame<M> {
+    /// Starts a new session on the given board, with the default rules.
+    pub fn new(board: M) -> Self {
+        Self::from_game(Game::new(board))
+    }
+
+    /// Shares the given session, keeping its rules and hooks.
+    pub fn from_game(mut game: Game<M>) -> Self {
+        let events = broadcast::channel(EVENTS_CAPACITY).0;
+        let sender = events.clone();
+        game.on_move(move |board, action, moves| {
+            // sending only fails if there are no subscribers
+            let _ = sender.send(MoveEvent {
+                action,
+                moves,
+                state: board.get_game_state(),
+            });
+        });
+        AsyncGame {
+            game: Arc::new(Mutex::new(game)),
+            events,
+        }
+    }
+
+    /// Returns a receiver of the moves made from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<MoveEvent> {
+        self.events.subscribe()
+    }
+
+    /// [Opens](Game::open) a cell, counting it as a move.
+    pub async fn open(&self, coord: Coordinate) -> Result<OpenResult> {
+        self.game.lock().await.open(coord)
+    }
+
+    /// [Toggles the flag](Game::toggle_flag) on a cell, counting it as a move.
+    pub async fn toggle_flag(&self, coord: Coordinate) -> Result<CellState> {
+        self.game.lock().await.toggle_flag(coord)
+    }
+
+    /// Makes the given move.
+    pub async fn play(&self, action: Move) -> Result<()> {
+        self.game.lock().await.play(action)
+    }
+
+    /// Returns the [state](MineSweeper::get_game_state) of the game.
+    pub async fn state(&self) -> GameState {
+        self.game.lock().await.board().get_game_state()
+    }
+
+    /// Returns the number of moves made so far.
+    pub async fn moves(&self) -> u64 {
+        self.game.lock().await.moves()
+    }
+
+    /// Locks the game, to read its board and its rules or to register hooks.
+    pub async fn lock(&self) -> MutexGuard<'_, Game<M>> {
+        self.game.lock().await
+    }
+}
+
+/// The asynchronous counterpart of [`MineSweeper`], available with the `tokio` feature.
+///
+/// It is implemented by [`AsyncGame`] for every board that can be sent between threads,
+/// so async code can be written once for all the implementations.
+pub trait AsyncMineSweeper: Send + Sync {
+    /// Asynchronous version of [`MineSweeper::open`].
+    fn open(&self, coord: Coordinate) -> impl Future<Output = Result<OpenResult>> + Send;
+    /// Asynchronous version of [`MineSweeper::toggle_flag`].
+    fn toggle_flag(&self, coord: Coordinate) -> impl Future<Output = Result<CellState>> + Send;
+    /// Asynchronous version of [`MineSweeper::get_cell`].
+    fn get_cell(&self, coord: Coordinate) -> impl Future<Output = Result<Cell>> + Send;
+    /// Asynchronous version of [`MineSweeper::get_game_state`].
+    fn get_game_state(&self) -> impl Future<Output = GameState> + Send;
+}
+
+impl<M: MineSweeper + Send> AsyncMineSweeper for AsyncGame<M> {
+    async fn open(&self, coord: Coordinate) -> Result<OpenResult> {
+        AsyncGame::open(self, coord).await
+    }
+
+    async fn toggle_flag(&self, coord: Coordinate) -> Result<CellState> {
+        AsyncGame::toggle_flag(self, coord).await
+    }
+
+    async fn get_cell(&self, coord: Coordinate) -> Result<Cell> {
+        self.lock().await.board().get_cell(coord)
+    }
+
+    async fn get_game_state(&self) -> GameState {
+        self.state().await
+    }
+}
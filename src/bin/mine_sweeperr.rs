@@ -0,0 +1,164 @@
+//! Plays a game in the terminal.
+//!
+//! Run with `cargo run --features cli -- --help` to see the options.
+
+use std::io::{self, BufRead, Write};
+
+use clap::{Parser, ValueEnum};
+use mine_sweeperr::{
+    neighbors,
+    solver::{safe_cells, NonDeterministic, SPSolver},
+    CellContent, CellState, Coordinate, Difficulty, MSMatrix, MineSweeper, MineSweeperExt, Pcg32,
+};
+
+#[derive(Parser)]
+#[command(about = "Plays mine sweeper in the terminal")]
+struct Args {
+    /// The size of the board. Ignored if height, width and mines are all given.
+    #[arg(short, long, value_enum, default_value_t = Level::Easy)]
+    difficulty: Level,
+    #[arg(long)]
+    height: Option<usize>,
+    #[arg(long)]
+    width: Option<usize>,
+    #[arg(long)]
+    mines: Option<usize>,
+    /// Places the mines with the given seed, to play the same game again.
+    #[arg(short, long)]
+    seed: Option<u64>,
+    /// Only generates boards that can be solved without guessing.
+    #[arg(long)]
+    no_guess: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Level {
+    Easy,
+    Medium,
+    Hard,
+}
+
+const HELP: &str = "commands: o <row> <column> (open), f <row> <column> (flag), h (hint), q (quit)";
+
+fn main() {
+    let args = Args::parse();
+    let difficulty = match (args.height, args.width, args.mines) {
+        (Some(height), Some(width), Some(mines)) => Difficulty::custom(height, width, mines),
+        _ => match args.difficulty {
+            Level::Easy => Difficulty::easy(),
+            Level::Medium => Difficulty::medium(),
+            Level::Hard => Difficulty::hard(),
+        },
+    };
+    let mut ms = match if args.no_guess {
+        MSMatrix::new_unstarted::<SPSolver<MSMatrix>>(difficulty)
+    } else {
+        MSMatrix::new_unstarted::<NonDeterministic>(difficulty)
+    } {
+        Ok(ms) => ms,
+        Err(error) => {
+            eprintln!("{error}");
+            return;
+        }
+    };
+
+    println!("{HELP}");
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        print!("{ms:.0}\nmines left: {}\n> ", ms.remaining_mines());
+        io::stdout().flush().unwrap();
+        let Some(Ok(line)) = lines.next() else {
+            return;
+        };
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let coord = match words[..] {
+            [_, r, c] => r.parse().ok().zip(c.parse().ok()),
+            _ => None,
+        };
+        let result = match (words.first().copied(), coord) {
+            (Some("o"), Some(coord)) => open(&mut ms, coord, args.seed, args.no_guess),
+            (Some("f"), Some(coord)) => ms.toggle_flag(coord).map(|_| false),
+            (Some("h"), _) => {
+                match hint(&ms) {
+                    Some((coord, true)) => println!("{coord:?} is a mine"),
+                    Some((coord, false)) => println!("{coord:?} is safe"),
+                    None => println!("no cell can be deduced, you have to guess"),
+                }
+                Ok(false)
+            }
+            (Some("q"), _) => return,
+            _ => {
+                println!("{HELP}");
+                Ok(false)
+            }
+        };
+        match result {
+            Ok(false) => {}
+            Ok(true) => {
+                println!("{ms:.0}\nBOOM! You lost");
+                return;
+            }
+            Err(error) => println!("{error}"),
+        }
+        if ms.is_cleared() {
+            println!("{ms:.0}\nYou won!");
+            return;
+        }
+    }
+}
+
+/// Opens a cell, placing the mines at the first move, and returns `true` if a mine exploded.
+fn open(
+    ms: &mut MSMatrix,
+    coord: Coordinate,
+    seed: Option<u64>,
+    no_guess: bool,
+) -> mine_sweeperr::Result<bool> {
+    if let (None, Some(seed)) = (ms.started_from(), seed) {
+        let rng = &mut Pcg32::new(seed);
+        if no_guess {
+            ms.place_mines_from_rng::<SPSolver<MSMatrix>>(coord, rng)?;
+        } else {
+            ms.place_mines_from_rng::<NonDeterministic>(coord, rng)?;
+        }
+    }
+    Ok(ms.open(coord)?.mines_exploded > 0)
+}
+
+/// Looks for a closed cell that is provably safe, or else for a mine proven by a single open number.
+/// Returns the cell and whether it is a mine.
+///
+/// Flags are not trusted, since they may be wrong: a number only proves its mines
+/// if all its neighbors that are not open must be mines.
+fn hint(ms: &MSMatrix) -> Option<(Coordinate, bool)> {
+    if let Some(&coord) = safe_cells(ms).first() {
+        return Some((coord, false));
+    }
+    let (height, width) = (ms.height(), ms.width());
+    (0..height)
+        .flat_map(|r| (0..width).map(move |c| (r, c)))
+        .find_map(|coord| {
+            let cell = ms.get_cell(coord).unwrap();
+            let (CellState::Open, CellContent::Number(n)) = (cell.state, cell.content) else {
+                return None;
+            };
+            let (mut unknown, mut exploded, mut first) = (0, 0, None);
+            for neighbor in neighbors(coord, height, width) {
+                let cell = ms.get_cell(neighbor).unwrap();
+                match (cell.state, cell.content) {
+                    (CellState::Open, CellContent::Mine) => exploded += 1,
+                    (CellState::Open, _) => {}
+                    (state, _) => {
+                        unknown += 1;
+                        // flagged mines don't need a hint
+                        if state == CellState::Closed {
+                            first = first.or(Some(neighbor));
+                        }
+                    }
+                }
+            }
+            let first = first?;
+            (n as usize == exploded + unknown).then_some((first, true))
+        })
+}
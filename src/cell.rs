@@ -1,4 +1,4 @@
-use std::fmt::{Display, Formatter};
+use core::fmt::{Display, Formatter};
 
 use crate::NUMBERS;
 
@@ -18,7 +18,7 @@ pub enum CellContent {
 }
 
 impl Display for CellContent {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         if f.alternate() {
             match self {
                 CellContent::Mine => write!(f, "🟥"),
@@ -62,6 +62,22 @@ impl Cell {
     pub const fn open() -> Self {
         Self::new(CellState::Open, CellContent::Number(0))
     }
+    /// Packs the cell in a single byte.
+    ///
+    /// The 4 lowest bits contain the number of neighboring mines, or `9` for a mine.
+    /// The next 2 bits contain the state: `0` for closed, `1` for open and `2` for flagged cells.
+    pub const fn to_byte(self) -> u8 {
+        let content = match self.content {
+            CellContent::Mine => 9,
+            CellContent::Number(n) => n,
+        };
+        let state = match self.state {
+            CellState::Closed => 0,
+            CellState::Open => 1,
+            CellState::Flagged => 2,
+        };
+        state << 4 | content
+    }
 }
 
 impl Default for Cell {
@@ -98,7 +114,7 @@ impl Display for Cell {
     /// When using monospace fonts, the non-emoji chars are perfectly aligned on columns
     /// but of course they are not the best way to print the grid.
     // some options are: 🟩 🟨 🟦 🟫 🟧 🟪 🟥
-    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
         if f.alternate() {
             match self.state {
                 CellState::Closed => write!(f, "🟪"),
@@ -0,0 +1,72 @@
+use crate::{Error, Result};
+
+/// A pair of zero-based coordinates. The first coordinate is the row, the second is the column.
+pub type Coordinate = (usize, usize);
+
+/// Conversion into a [`Coordinate`](Coordinate) from pairs of integers that always fit in a `usize`.
+///
+/// Frontends rarely work with `usize` natively, so this is implemented for pairs of
+/// `u8`, `u16` and `u32`.
+/// ```
+/// # use mine_sweeperr::IntoCoordinate;
+/// assert_eq!((3_u32, 4_u32).into_coordinate(), (3, 4));
+/// ```
+pub trait IntoCoordinate {
+    fn into_coordinate(self) -> Coordinate;
+}
+
+/// Fallible conversion into a [`Coordinate`](Coordinate) from pairs of signed integers.
+///
+/// Returns [`InvalidCoordinate`](Error::InvalidCoordinate) if one of the two values is negative
+/// or doesn't fit in a `usize` on the current platform.
+/// Every type implementing [`IntoCoordinate`](IntoCoordinate) also implements this trait.
+/// ```
+/// # use mine_sweeperr::{Error, TryIntoCoordinate};
+/// assert_eq!((3_i32, 4_i32).try_into_coordinate(), Ok((3, 4)));
+/// assert_eq!(
+///     (-1_i64, 4_i64).try_into_coordinate(),
+///     Err(Error::InvalidCoordinate { row: -1, column: 4 })
+/// );
+/// ```
+pub trait TryIntoCoordinate {
+    fn try_into_coordinate(self) -> Result<Coordinate>;
+}
+
+macro_rules! impl_into_coordinate {
+    ($($t:ty),*) => {
+        $(
+            impl IntoCoordinate for ($t, $t) {
+                fn into_coordinate(self) -> Coordinate {
+                    (self.0 as usize, self.1 as usize)
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_try_into_coordinate {
+    ($($t:ty),*) => {
+        $(
+            impl TryIntoCoordinate for ($t, $t) {
+                fn try_into_coordinate(self) -> Result<Coordinate> {
+                    match (usize::try_from(self.0), usize::try_from(self.1)) {
+                        (Ok(row), Ok(column)) => Ok((row, column)),
+                        _ => Err(Error::InvalidCoordinate {
+                            row: self.0 as i64,
+                            column: self.1 as i64,
+                        }),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_into_coordinate!(u8, u16, u32);
+impl_try_into_coordinate!(i8, i16, i32, i64);
+
+impl<T: IntoCoordinate> TryIntoCoordinate for T {
+    fn try_into_coordinate(self) -> Result<Coordinate> {
+        Ok(self.into_coordinate())
+    }
+}
@@ -1,3 +1,6 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 /// Represents the difficulty of a game in terms of height, width and number of mines.
 ///
 /// When calling [`MineSweeper::new`](crate::MineSweeper::new) or [`MineSweeper::from_rng`](crate::MineSweeper::from_rng)
@@ -18,6 +21,7 @@
 /// ```
 /// will produce a difficulty with `10x10` grid and `10` mines.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Difficulty {
     height: usize,
     width: usize,
@@ -0,0 +1,343 @@
+//! A playable board for [`egui`](::egui) applications.
+
+use std::{hash::Hash, time::Duration};
+
+use egui::{
+    vec2, Align2, Color32, FontId, Id, Rect, Response, Sense, Stroke, StrokeKind, Ui, Widget,
+};
+
+use crate::{frontend::InputTranslator, CellContent, CellState, MineSweeper, MineSweeperExt};
+
+/// A widget drawing a board with its mine counter and timer.
+///
+/// A left click opens a cell, or chords if it is already open, and a right click toggles a flag.
+/// Invalid moves, like flagging an open cell, are ignored.
+///
+/// The timer starts when the first cell is opened and stops when the game is won or a mine is opened.
+/// It is kept in the memory of the [`Context`](egui::Context) between frames,
+/// and restarts when a board without open cells is shown.
+/// To show more than one board in the same [`Ui`], give each of them its own [`id_salt`](EguiBoard::id_salt).
+///
+/// ```no_run
+/// # use mine_sweeperr::{egui::EguiBoard, MSMatrix};
+/// # fn show(ui: &mut egui::Ui, ms: &mut MSMatrix) {
+/// ui.add(EguiBoard::new(ms));
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct EguiBoard<'a, M: MineSweeper> {
+    ms: &'a mut M,
+    cell_size: f32,
+    elapsed: Option<Duration>,
+    id_salt: Id,
+}
+
+impl<'a, M: MineSweeper> EguiBoard<'a, M> {
+    pub fn new(ms: &'a mut M) -> Self {
+        EguiBoard {
+            ms,
+            cell_size: 24.0,
+            elapsed: None,
+            id_salt: Id::new("mine_sweeperr::EguiBoard"),
+        }
+    }
+
+    /// Sets the side of a cell, in points. The default is `24`.
+    pub fn cell_size(mut self, cell_size: f32) -> Self {
+        self.cell_size = cell_size;
+        self
+    }
+
+    /// Shows the given time next to the mine counter, instead of the time measured by the widget.
+    pub fn timer(mut self, elapsed: Duration) -> Self {
+        self.elapsed = Some(elapsed);
+        self
+    }
+
+    /// Sets the source of the id under which the timer is kept, which must be unique in the [`Ui`].
+    /// The default is the same for all the boards.
+    pub fn id_salt(mut self, id_salt: impl Hash) -> Self {
+        self.id_salt = Id::new(id_salt);
+        self
+    }
+}
+
+impl<M: MineSweeper> Widget for EguiBoard<'_, M> {
+    /// Draws the board and plays the move requested by the player, if any.
+    fn ui(self, ui: &mut Ui) -> Response {
+        let timer_id = timer_id(ui, self.id_salt);
+        let now = ui.input(|input| input.time);
+        let mut timer = ui.data(|data| data.get_temp::<Timer>(timer_id).unwrap_or_default());
+        ui.horizontal(|ui| {
+            ui.label(format!("💣 {}", self.ms.get_game_state().mines_left));
+            let elapsed = self.elapsed.unwrap_or_else(|| timer.elapsed(now));
+            ui.label(format!("⏱ {}", elapsed.as_secs()));
+        });
+        let size = vec2(
+            self.ms.width() as f32 * self.cell_size,
+            self.ms.height() as f32 * self.cell_size,
+        );
+        let (rect, response) = ui.allocate_exact_size(size, Sense::click());
+        let translator = Translator {
+            rect,
+            cell_size: self.cell_size,
+        };
+        if let Some(coord) = response
+            .interact_pointer_pos()
+            .and_then(|pos| translator.to_coordinate(&*self.ms, (pos.x, pos.y)))
+        {
+            if response.clicked() {
+                let _ = self.ms.open(coord);
+            } else if response.secondary_clicked() {
+                let _ = self.ms.toggle_flag(coord);
+            }
+        }
+
+        let state = self.ms.get_game_state();
+        let over = state.exploded > 0 || self.ms.is_cleared();
+        // the labels are drawn before the move is played, so a change is shown at the next frame
+        let before = timer;
+        timer.update(now, state.opened > 0, over);
+        if timer != before {
+            ui.ctx().request_repaint();
+        } else if timer.is_running() {
+            ui.ctx().request_repaint_after(Duration::from_secs(1));
+        }
+        ui.data_mut(|data| data.insert_temp(timer_id, timer));
+
+        let painter = ui.painter_at(rect);
+        let width = self.ms.width();
+        for (i, cell) in self.ms.cells_snapshot().into_iter().enumerate() {
+            let min = rect.min + vec2((i % width) as f32, (i / width) as f32) * self.cell_size;
+            let cell_rect = Rect::from_min_size(min, vec2(self.cell_size, self.cell_size));
+            let (fill, text) = match (cell.state, cell.content) {
+                (CellState::Closed, _) => (Color32::GRAY, String::new()),
+                (CellState::Flagged, _) => (Color32::GRAY, "🚩".to_string()),
+                (CellState::Open, CellContent::Mine) => (Color32::RED, "💣".to_string()),
+                (CellState::Open, CellContent::Number(0)) => (Color32::LIGHT_GRAY, String::new()),
+                (CellState::Open, CellContent::Number(n)) => (Color32::LIGHT_GRAY, n.to_string()),
+            };
+            painter.rect(
+                cell_rect,
+                0.0,
+                fill,
+                Stroke::new(1.0, Color32::DARK_GRAY),
+                StrokeKind::Inside,
+            );
+            painter.text(
+                cell_rect.center(),
+                Align2::CENTER_CENTER,
+                text,
+                FontId::monospace(self.cell_size * 0.6),
+                Color32::BLACK,
+            );
+        }
+        response
+    }
+}
+
+/// The key of the [`Timer`] of the boards with the given salt drawn in the given ui.
+fn timer_id(ui: &Ui, id_salt: Id) -> Id {
+    ui.make_persistent_id(id_salt.with("timer"))
+}
+
+/// The times, from [`InputState::time`](egui::InputState::time), at which the game started and ended.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Timer {
+    started: Option<f64>,
+    stopped: Option<f64>,
+}
+
+impl Timer {
+    /// Starts the timer at the first open cell, stops it when the game is over
+    /// and resets it when no cell is open.
+    fn update(&mut self, now: f64, started: bool, over: bool) {
+        if !started {
+            *self = Timer::default();
+            return;
+        }
+        let started = *self.started.get_or_insert(now);
+        if over && self.stopped.is_none() {
+            self.stopped = Some(now.max(started));
+        }
+    }
+
+    fn is_running(&self) -> bool {
+        self.started.is_some() && self.stopped.is_none()
+    }
+
+    fn elapsed(&self, now: f64) -> Duration {
+        match self.started {
+            Some(started) => {
+                Duration::from_secs_f64((self.stopped.unwrap_or(now) - started).max(0.0))
+            }
+            None => Duration::ZERO,
+        }
+    }
+}
+
+struct Translator {
+    rect: Rect,
+    cell_size: f32,
+}
+
+impl InputTranslator for Translator {
+    fn cell_size(&self) -> (f32, f32) {
+        (self.cell_size, self.cell_size)
+    }
+
+    fn origin(&self) -> (f32, f32) {
+        (self.rect.min.x, self.rect.min.y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use egui::{
+        pos2, vec2, CentralPanel, Context, Event, Id, Modifiers, PointerButton, Pos2, RawInput,
+        Rect, Ui,
+    };
+
+    use super::{timer_id, EguiBoard, Timer};
+    use crate::{board, CellState, MSMatrix, MineSweeper};
+
+    /// Runs a frame at the given time with the given events,
+    /// and returns the area of the board with the time shown by its timer.
+    fn frame_at(
+        ctx: &Context,
+        ms: &mut MSMatrix,
+        events: Vec<Event>,
+        time: f64,
+    ) -> (Rect, Duration) {
+        let (mut rect, mut elapsed) = (Rect::NOTHING, Duration::ZERO);
+        let input = RawInput {
+            events,
+            time: Some(time),
+            ..Default::default()
+        };
+        let _ = ctx.run(input, |ctx| {
+            CentralPanel::default().show(ctx, |ui| {
+                let board = EguiBoard::new(ms).cell_size(20.0);
+                let id_salt = board.id_salt;
+                rect = ui.add(board).rect;
+                elapsed = timer_at(ui, id_salt, time);
+            });
+        });
+        (rect, elapsed)
+    }
+
+    /// Returns the time shown by the timer of the boards with the given salt.
+    fn timer_at(ui: &Ui, id_salt: Id, time: f64) -> Duration {
+        let timer: Timer = ui.data(|data| data.get_temp(timer_id(ui, id_salt)).unwrap_or_default());
+        timer.elapsed(time)
+    }
+
+    fn frame(ctx: &Context, ms: &mut MSMatrix, events: Vec<Event>) -> Rect {
+        frame_at(ctx, ms, events, 0.0).0
+    }
+
+    fn click_at(ctx: &Context, ms: &mut MSMatrix, pos: Pos2, button: PointerButton, time: f64) {
+        for pressed in [true, false] {
+            let event = Event::PointerButton {
+                pos,
+                button,
+                pressed,
+                modifiers: Modifiers::NONE,
+            };
+            frame_at(ctx, ms, vec![Event::PointerMoved(pos), event], time);
+        }
+    }
+
+    fn click(ctx: &Context, ms: &mut MSMatrix, pos: Pos2, button: PointerButton) {
+        click_at(ctx, ms, pos, button, 0.0);
+    }
+
+    #[test]
+    fn clicks() {
+        let mut ms = board!["*..", "..."];
+        let ctx = Context::default();
+        let rect = frame(&ctx, &mut ms, vec![]);
+        assert_eq!(rect.size(), vec2(60.0, 40.0));
+
+        click(&ctx, &mut ms, pos2(1000.0, 1000.0), PointerButton::Primary);
+        assert_eq!(ms.get_game_state().opened, 0);
+
+        click(
+            &ctx,
+            &mut ms,
+            rect.min + vec2(50.0, 30.0),
+            PointerButton::Secondary,
+        );
+        assert_eq!(ms.get_cell((1, 2)).unwrap().state, CellState::Flagged);
+
+        click(
+            &ctx,
+            &mut ms,
+            rect.min + vec2(30.0, 10.0),
+            PointerButton::Primary,
+        );
+        assert_eq!(ms.get_cell((0, 1)).unwrap().state, CellState::Open);
+    }
+
+    #[test]
+    fn timer() {
+        let mut ms = board!["*..", "..."];
+        let ctx = Context::default();
+        let (rect, elapsed) = frame_at(&ctx, &mut ms, vec![], 1.0);
+        assert_eq!(elapsed, Duration::ZERO);
+
+        // starts at the first open
+        let cell = |r: f32, c: f32| rect.min + vec2(c * 20.0 + 10.0, r * 20.0 + 10.0);
+        click_at(&ctx, &mut ms, cell(1.0, 2.0), PointerButton::Primary, 2.0);
+        assert_eq!(
+            frame_at(&ctx, &mut ms, vec![], 5.0).1,
+            Duration::from_secs(3)
+        );
+
+        // stops when the game is won
+        click_at(&ctx, &mut ms, cell(1.0, 0.0), PointerButton::Primary, 7.0);
+        assert_eq!(ms.get_game_state().opened, 5);
+        assert_eq!(
+            frame_at(&ctx, &mut ms, vec![], 20.0).1,
+            Duration::from_secs(5)
+        );
+
+        // and when a mine is opened, restarting on a new board
+        let mut ms = board!["*..", "..."];
+        assert_eq!(frame_at(&ctx, &mut ms, vec![], 21.0).1, Duration::ZERO);
+        click_at(&ctx, &mut ms, cell(1.0, 2.0), PointerButton::Primary, 22.0);
+        click_at(&ctx, &mut ms, cell(0.0, 0.0), PointerButton::Primary, 24.0);
+        assert_eq!(
+            frame_at(&ctx, &mut ms, vec![], 30.0).1,
+            Duration::from_secs(2)
+        );
+    }
+
+    #[test]
+    fn id_salt() {
+        let (mut started, mut unstarted) = (board!["*..", "..."], board!["*..", "..."]);
+        started.open((1, 2)).unwrap();
+        let ctx = Context::default();
+        let mut elapsed = (Duration::ZERO, Duration::ZERO);
+        for time in [1.0, 4.0] {
+            let input = RawInput {
+                time: Some(time),
+                ..Default::default()
+            };
+            let _ = ctx.run(input, |ctx| {
+                CentralPanel::default().show(ctx, |ui| {
+                    ui.add(EguiBoard::new(&mut started).id_salt("started"));
+                    ui.add(EguiBoard::new(&mut unstarted).id_salt("unstarted"));
+                    elapsed = (
+                        timer_at(ui, Id::new("started"), time),
+                        timer_at(ui, Id::new("unstarted"), time),
+                    );
+                });
+            });
+        }
+        // the board without open cells doesn't restart the timer of the other one
+        assert_eq!(elapsed, (Duration::from_secs(3), Duration::ZERO));
+    }
+}
@@ -0,0 +1,193 @@
+//! A reinforcement learning environment in the style of [Gym](https://gymnasium.farama.org).
+//!
+//! Each episode is a game on a new board: [`reset`](Environment::reset) starts it
+//! and [`step`](Environment::step) plays one move, returning what the agent can see,
+//! the reward of the move and whether the episode is over.
+//!
+//! ```
+//! use mine_sweeperr::{env::Environment, Difficulty, MSMatrix, Move};
+//!
+//! let mut env = Environment::<MSMatrix>::new(Difficulty::easy(), 42);
+//! let observation = env.reset();
+//! assert_eq!(observation.opened, 0);
+//! let (observation, reward, done) = env.step(Move::Open((4, 4)));
+//! assert!(observation.opened > 0 && reward > 0.0 && !done);
+//! ```
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::{
+    save::place_mines_before, BoardView, Difficulty, MineSweeper, MineSweeperExt, Move, Pcg32,
+    Random,
+};
+
+/// What the agent sees after each step.
+pub type Observation = BoardView;
+
+/// A move of the agent.
+pub type Action = Move;
+
+/// The reward of a single step.
+pub type Reward = f64;
+
+/// `true` if the episode is over.
+pub type Done = bool;
+
+/// The rewards given to the agent, used to shape its behaviour.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rewards {
+    /// Given when all the safe cells are open.
+    pub win: Reward,
+    /// Given when a mine is opened.
+    pub loss: Reward,
+    /// Given for each cell opened by a move, except the mines that end the game.
+    pub cell: Reward,
+    /// Given for each move, for example a small negative value to encourage shorter games.
+    pub step: Reward,
+    /// Given instead of the other rewards when the move is not allowed.
+    pub invalid: Reward,
+}
+
+impl Default for Rewards {
+    /// Rewards winning with `1`, losing with `-1`, each opened cell with `0.01`
+    /// and invalid moves with `-0.1`.
+    fn default() -> Self {
+        Rewards {
+            win: 1.0,
+            loss: -1.0,
+            cell: 0.01,
+            step: 0.0,
+            invalid: -0.1,
+        }
+    }
+}
+
+/// An environment playing games on boards with the same difficulty.
+///
+/// The boards are generated from a seed, so the same sequence of episodes can be repeated.
+#[derive(Debug, Clone)]
+pub struct Environment<M: MineSweeper> {
+    difficulty: Difficulty,
+    rewards: Rewards,
+    rng: Pcg32,
+    board: M,
+    board_seed: u64,
+    done: bool,
+}
+
+impl<M: MineSweeper> Environment<M> {
+    /// Creates an environment with the [default rewards](Rewards::default).
+    ///
+    /// # Panics
+    /// Panics if the difficulty is not [valid](crate::validate_difficulty).
+    pub fn new(difficulty: Difficulty, seed: u64) -> Self {
+        Self::with_rewards(difficulty, seed, Rewards::default())
+    }
+
+    /// Creates an environment with the given rewards.
+    ///
+    /// # Panics
+    /// Panics if the difficulty is not [valid](crate::validate_difficulty).
+    pub fn with_rewards(difficulty: Difficulty, seed: u64, rewards: Rewards) -> Self {
+        let mut rng = Pcg32::new(seed);
+        Environment {
+            difficulty,
+            rewards,
+            board_seed: rng.next_u64(),
+            rng,
+            board: M::new_unstarted(difficulty).expect("invalid difficulty"),
+            done: false,
+        }
+    }
+
+    /// Returns the rewards of this environment.
+    pub fn rewards(&self) -> Rewards {
+        self.rewards
+    }
+
+    /// Returns the board of the current episode.
+    pub fn board(&self) -> &M {
+        &self.board
+    }
+
+    /// Returns `true` if the current episode is over.
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Returns what the agent currently sees.
+    pub fn observation(&self) -> Observation {
+        BoardView::new(&self.board)
+    }
+
+    /// Starts a new episode on a new board, with the mines placed at the first open.
+    pub fn reset(&mut self) -> Observation {
+        self.board = M::new_unstarted(self.difficulty).expect("invalid difficulty");
+        self.board_seed = self.rng.next_u64();
+        self.done = false;
+        self.observation()
+    }
+
+    /// Plays a move in the current episode.
+    ///
+    /// Moves that are not allowed or that open no cells get the [invalid](Rewards::invalid) reward.
+    /// This includes all the moves after the end of an episode, until the next [reset](Self::reset).
+    pub fn step(&mut self, action: Action) -> (Observation, Reward, Done) {
+        let reward = match self.play(action) {
+            Some(reward) => reward + self.rewards.step,
+            None => self.rewards.invalid,
+        };
+        (self.observation(), reward, self.done)
+    }
+
+    /// Plays a move, returning its reward or `None` if the move is not allowed or has no effect.
+    fn play(&mut self, action: Action) -> Option<Reward> {
+        if self.done {
+            return None;
+        }
+        match action {
+            Move::Open(coord) => {
+                place_mines_before(&mut self.board, self.board_seed, action).ok()?;
+                let result = self.board.open(coord).ok()?;
+                if result.cells_opened == 0 {
+                    return None;
+                }
+                if result.mines_exploded > 0 {
+                    self.done = true;
+                    let opened = result.cells_opened - result.mines_exploded;
+                    Some(self.rewards.loss + self.rewards.cell * opened as Reward)
+                } else {
+                    self.done = self.board.is_cleared();
+                    let win = if self.done { self.rewards.win } else { 0.0 };
+                    Some(win + self.rewards.cell * result.cells_opened as Reward)
+                }
+            }
+            Move::ToggleFlag(coord) => self.board.toggle_flag(coord).ok().map(|_| 0.0),
+        }
+    }
+}
+
+/// Plays one move in each environment in parallel on the [rayon] thread pool,
+/// returning the results in the same order.
+/// Environments that are [done](Environment::is_done) are [reset](Environment::reset) first,
+/// so each of them can play an endless sequence of episodes.
+///
+/// # Panics
+/// Panics if the number of actions is not the same as the number of environments.
+#[cfg(feature = "rayon")]
+pub fn step_all<M: MineSweeper + Send>(
+    envs: &mut [Environment<M>],
+    actions: &[Action],
+) -> Vec<(Observation, Reward, Done)> {
+    assert_eq!(envs.len(), actions.len(), "one action per environment");
+    envs.par_iter_mut()
+        .zip(actions)
+        .map(|(env, &action)| {
+            if env.is_done() {
+                env.reset();
+            }
+            env.step(action)
+        })
+        .collect()
+}
@@ -0,0 +1,143 @@
+use core::fmt::{Display, Formatter};
+
+use crate::Coordinate;
+
+/// The category of an [`Error`](Error).
+///
+/// Every new variant of [`Error`](Error) will belong to one of these categories,
+/// so matching on them is stable across releases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The parameters used to create a new game are not valid.
+    Setup,
+    /// A coordinate doesn't belong to the board.
+    Coordinate,
+    /// The requested move is not allowed in the current state of the game.
+    Move,
+}
+
+/// Error type for the [`MineSweeper`](crate::MineSweeper) game.
+///
+/// New variants may be added in the future, so matches on this type should have a wildcard arm.
+/// To handle whole groups of errors in a way that keeps working when new variants are added,
+/// match on their [`kind`](Error::kind) instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The given coordinate is outside of a board with the given size.
+    OutOfBounds {
+        coord: Coordinate,
+        height: usize,
+        width: usize,
+    },
+    AlreadyOpen,
+    /// The requested number of mines is greater than the maximum allowed for the size of the board.
+    TooManyMines {
+        mines: usize,
+        max: usize,
+    },
+    InvalidParameters,
+    /// The given pair of integers can't be converted to a [`Coordinate`](Coordinate),
+    /// because one of them is negative or too big.
+    InvalidCoordinate {
+        row: i64,
+        column: i64,
+    },
+    /// The mines of the board have already been placed.
+    AlreadyStarted,
+    /// All the mines are already flagged and the [rules](crate::GameRules::flag_limit)
+    /// don't allow more flags.
+    TooManyFlags,
+    /// The game is lost and no more moves can be made.
+    GameOver,
+    /// The seed needed to restore a [saved game](crate::SavedGame) has been removed.
+    MissingSeed,
+    /// The mines of the board have not been placed yet.
+    NotStarted,
+    /// The [number rule](crate::NumberRule) is not supported by the solver or the implementation.
+    UnsupportedNumberRule,
+    /// Some cells of the board are already open, so it can't be changed anymore.
+    CellsAlreadyOpen,
+    /// The [storage](crate::GridStorage) of the implementation can't hold a board with the given size,
+    /// since it has room for `capacity` cells, or only for boards of another size like [`MSConst`](crate::MSConst).
+    SizeMismatch {
+        height: usize,
+        width: usize,
+        capacity: usize,
+    },
+}
+
+impl Error {
+    /// Returns the category of the error.
+    pub const fn kind(&self) -> ErrorKind {
+        match self {
+            Error::TooManyMines { .. }
+            | Error::InvalidParameters
+            | Error::MissingSeed
+            | Error::UnsupportedNumberRule
+            | Error::SizeMismatch { .. } => ErrorKind::Setup,
+            Error::OutOfBounds { .. } | Error::InvalidCoordinate { .. } => ErrorKind::Coordinate,
+            Error::AlreadyOpen
+            | Error::AlreadyStarted
+            | Error::TooManyFlags
+            | Error::GameOver
+            | Error::NotStarted
+            | Error::CellsAlreadyOpen => ErrorKind::Move,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Error::OutOfBounds {
+                coord,
+                height,
+                width,
+            } => write!(
+                f,
+                "the coordinate {:?} is out of the bounds of a {}x{} board",
+                coord, height, width
+            ),
+            Error::AlreadyOpen => write!(f, "the cell is already open"),
+            Error::TooManyMines { mines, max } => write!(
+                f,
+                "too many mines: {} requested but at most {} are allowed, \
+                since 9 cells around the starting point must be safe",
+                mines, max
+            ),
+            Error::InvalidParameters => {
+                write!(
+                    f,
+                    "the board must have at least one row and one column, \
+                    and no more cells than a usize can count"
+                )
+            }
+            Error::InvalidCoordinate { row, column } => write!(
+                f,
+                "the pair ({}, {}) can't be converted to a coordinate",
+                row, column
+            ),
+            Error::AlreadyStarted => write!(f, "the mines have already been placed"),
+            Error::TooManyFlags => write!(f, "there are already as many flags as mines"),
+            Error::GameOver => write!(f, "the game is over"),
+            Error::MissingSeed => write!(f, "the seed of the game is missing"),
+            Error::NotStarted => write!(f, "the mines have not been placed yet"),
+            Error::UnsupportedNumberRule => write!(f, "the number rule is not supported"),
+            Error::CellsAlreadyOpen => write!(f, "some cells of the board are already open"),
+            Error::SizeMismatch {
+                height,
+                width,
+                capacity,
+            } => write!(
+                f,
+                "a {}x{} board doesn't fit in a storage made for {} cells",
+                height, width, capacity
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
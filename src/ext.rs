@@ -0,0 +1,100 @@
+use crate::{neighbors, CellContent, CellState, Coordinate, MineSweeper, Result};
+use alloc::vec;
+
+/// Convenience methods available on every [`MineSweeper`],
+/// to answer simple questions without matching on [`Cell`](crate::Cell).
+///
+#[cfg_attr(feature = "std", doc = "```")]
+#[cfg_attr(not(feature = "std"), doc = "```ignore")]
+/// use mine_sweeperr::{board, MineSweeper, MineSweeperExt};
+///
+/// let mut ms = board!["*..", "...", "..."];
+/// ms.toggle_flag((0, 0)).unwrap();
+/// assert!(ms.is_mine((0, 0)).unwrap() && ms.is_flagged((0, 0)).unwrap());
+/// assert_eq!(ms.remaining_mines(), 0);
+/// assert_eq!(ms.closed_cells(), 8);
+/// ms.open((2, 2)).unwrap();
+/// assert!(ms.is_cleared());
+/// ```
+pub trait MineSweeperExt: MineSweeper {
+    /// Returns `true` if the cell contains a mine, whatever its state.
+    /// If the coordinates are out of bounds returns [`OutOfBounds`](crate::Error::OutOfBounds).
+    fn is_mine(&self, coord: Coordinate) -> Result<bool> {
+        Ok(self.get_cell(coord)?.content == CellContent::Mine)
+    }
+
+    /// Returns `true` if the cell is open.
+    /// If the coordinates are out of bounds returns [`OutOfBounds`](crate::Error::OutOfBounds).
+    fn is_open(&self, coord: Coordinate) -> Result<bool> {
+        Ok(self.get_cell(coord)?.state == CellState::Open)
+    }
+
+    /// Returns `true` if the cell is flagged.
+    /// If the coordinates are out of bounds returns [`OutOfBounds`](crate::Error::OutOfBounds).
+    fn is_flagged(&self, coord: Coordinate) -> Result<bool> {
+        Ok(self.get_cell(coord)?.state == CellState::Flagged)
+    }
+
+    /// Returns the number of mines minus the number of flags and exploded mines,
+    /// as in [`GameState::mines_left`](crate::GameState::mines_left).
+    fn remaining_mines(&self) -> usize {
+        self.get_game_state().mines_left
+    }
+
+    /// Returns the number of cells that are neither open nor flagged.
+    fn closed_cells(&self) -> usize {
+        let state = self.get_game_state();
+        self.height() * self.width() - state.opened - state.flagged
+    }
+
+    /// Returns the number of safe cells that still have to be opened to win the game.
+    /// Exploded mines are not counted as opened safe cells.
+    fn safe_cells_remaining(&self) -> usize {
+        let state = self.get_game_state();
+        self.height() * self.width() - self.mines() - (state.opened - state.exploded)
+    }
+
+    /// Returns `true` if all the safe cells are open, which is the winning condition of the game.
+    fn is_cleared(&self) -> bool {
+        self.safe_cells_remaining() == 0
+    }
+
+    /// Iterates over the closed cells next to at least one open number, in row-major order,
+    /// together with an iterator over the coordinates and the values of those numbers.
+    /// Flagged cells are not yielded.
+    ///
+    /// These are the cells that can be deduced from the numbers shown to the player.
+    /// They are found from the closed neighbors of the open numbers,
+    /// and the numbers next to each cell are read lazily, without allocating.
+    fn iter_frontier(
+        &self,
+    ) -> impl Iterator<Item = (Coordinate, impl Iterator<Item = (Coordinate, u8)> + '_)> + '_ {
+        let (height, width) = (self.height(), self.width());
+        let cells = self.cells_snapshot();
+        let mut frontier = vec![false; cells.len()];
+        for (i, cell) in cells.iter().enumerate() {
+            if let (CellState::Open, CellContent::Number(_)) = (cell.state, cell.content) {
+                for (r, c) in neighbors((i / width, i % width), height, width) {
+                    frontier[r * width + c] |= cells[r * width + c].state == CellState::Closed;
+                }
+            }
+        }
+        frontier
+            .into_iter()
+            .enumerate()
+            .filter(|&(_, closed)| closed)
+            .map(move |(i, _)| {
+                let coord = (i / width, i % width);
+                let numbers = neighbors(coord, height, width).filter_map(move |neighbor| {
+                    let cell = self.get_cell(neighbor).unwrap();
+                    match (cell.state, cell.content) {
+                        (CellState::Open, CellContent::Number(n)) => Some((neighbor, n)),
+                        _ => None,
+                    }
+                });
+                (coord, numbers)
+            })
+    }
+}
+
+impl<M: MineSweeper> MineSweeperExt for M {}
@@ -0,0 +1,51 @@
+//! Adapters shared by the frontends built on top of this crate.
+//!
+//! Engine plugins only need to choose a sprite for each [`Cell`](Cell)
+//! and the size of a cell on screen: iterating over the board and mapping pointer positions
+//! to coordinates is done here.
+
+use crate::{Cell, Coordinate, MineSweeper};
+
+/// Draws a board one cell at a time.
+pub trait BoardRenderer {
+    /// The identifier of a sprite, like an index in a texture atlas.
+    type Sprite;
+
+    /// Returns the sprite to use for a cell.
+    fn sprite(&self, cell: Cell) -> Self::Sprite;
+
+    /// Draws a sprite at the given coordinate.
+    fn draw(&mut self, coord: Coordinate, sprite: Self::Sprite);
+
+    /// Draws all the cells of the board, in row-major order.
+    fn render(&mut self, ms: &impl MineSweeper) {
+        let width = ms.width();
+        for (i, cell) in ms.cells_snapshot().into_iter().enumerate() {
+            let sprite = self.sprite(cell);
+            self.draw((i / width, i % width), sprite);
+        }
+    }
+}
+
+/// Maps pointer positions to coordinates on the board.
+pub trait InputTranslator {
+    /// Returns the width and height of a cell on screen.
+    fn cell_size(&self) -> (f32, f32);
+
+    /// Returns the position on screen of the top-left corner of the board.
+    fn origin(&self) -> (f32, f32) {
+        (0.0, 0.0)
+    }
+
+    /// Returns the coordinate of the cell under the pointer,
+    /// or `None` if the pointer is outside of the board.
+    fn to_coordinate(&self, ms: &impl MineSweeper, (x, y): (f32, f32)) -> Option<Coordinate> {
+        let ((cell_width, cell_height), (left, top)) = (self.cell_size(), self.origin());
+        let (column, row) = ((x - left) / cell_width, (y - top) / cell_height);
+        if row < 0.0 || column < 0.0 {
+            return None;
+        }
+        let coord = (row as usize, column as usize);
+        (coord.0 < ms.height() && coord.1 < ms.width()).then_some(coord)
+    }
+}
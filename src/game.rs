@@ -0,0 +1,402 @@
+use std::{
+    collections::HashSet,
+    fmt::{Debug, Formatter},
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    save::place_mines_before, CellState, Coordinate, Error, GameRules, MineSweeper, MineSweeperExt,
+    OpenResult, Result, SavedGame, SpectatorView, StartPolicy, Storage,
+};
+
+/// A move made by the player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum Move {
+    /// [Opens](MineSweeper::open) a cell.
+    Open(Coordinate),
+    /// [Toggles the flag](MineSweeper::toggle_flag) on a cell.
+    ToggleFlag(Coordinate),
+}
+
+impl Move {
+    /// Makes this move on the given board.
+    pub fn play(self, ms: &mut impl MineSweeper) -> Result<()> {
+        match self {
+            Move::Open(coord) => ms.open(coord).map(drop),
+            Move::ToggleFlag(coord) => ms.toggle_flag(coord).map(drop),
+        }
+    }
+}
+
+/// When a [`Game`] is [autosaved](Game::autosave).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AutosavePolicy {
+    /// Saves after the given number of moves since the last save.
+    EveryMoves(u64),
+    /// Saves at the first move made after the given time since the last save.
+    Every(Duration),
+}
+
+/// A function called after each move, with the board, the move and the number of moves made so far.
+///
+/// Hooks are [`Send`], so that a game can be shared between threads like [`AsyncGame`](crate::AsyncGame) does.
+pub type MoveHook<M> = Box<dyn FnMut(&M, Move, u64) + Send>;
+
+/// A game session wrapping a board.
+///
+/// Counts the moves made by the player and calls the registered [hooks](Game::on_move) after each one,
+/// which can be used for autosaving, rate-limiting or keeping a server in sync.
+/// Only successful moves are counted.
+///
+/// The session also enforces the optional [rules](GameRules) of the game.
+pub struct Game<M: MineSweeper> {
+    board: M,
+    rules: GameRules,
+    moves: u64,
+    exploded: usize,
+    question_marks: HashSet<Coordinate>,
+    seed: Option<u64>,
+    hooks: Vec<MoveHook<M>>,
+}
+
+impl<M: MineSweeper> Game<M> {
+    /// Starts a new session on the given board.
+    pub fn new(board: M) -> Self {
+        Self::with_rules(board, GameRules::default())
+    }
+
+    /// Starts a new session on the given board, playing with the given rules.
+    ///
+    /// With [`StartPolicy::OpenStart`] the starting cell is opened as the first move,
+    /// before any hook can be registered: use a [`GameBuilder`] to pass that move to the hooks too.
+    pub fn with_rules(board: M, rules: GameRules) -> Self {
+        GameBuilder::new(board).rules(rules).build()
+    }
+
+    /// Returns a [builder](GameBuilder) to choose the rules and the hooks of a session on the given board.
+    pub fn builder(board: M) -> GameBuilder<M> {
+        GameBuilder::new(board)
+    }
+
+    /// Returns the rules of this session.
+    pub fn rules(&self) -> GameRules {
+        self.rules
+    }
+
+    /// Returns the number of mines that can still be opened before the game is lost.
+    pub fn lives_left(&self) -> usize {
+        self.rules.lives.saturating_sub(self.exploded)
+    }
+
+    /// Returns `true` if the player has run out of [lives](GameRules::lives).
+    pub fn is_lost(&self) -> bool {
+        self.lives_left() == 0
+    }
+
+    /// Returns `true` if the given cell is marked with a [question mark](GameRules::question_marks).
+    /// The board sees these cells as [closed](CellState::Closed).
+    pub fn is_question_marked(&self, coord: Coordinate) -> bool {
+        self.question_marks.contains(&coord)
+    }
+
+    /// Returns the board of this session.
+    pub fn board(&self) -> &M {
+        &self.board
+    }
+
+    /// Ends the session returning its board.
+    pub fn into_board(self) -> M {
+        self.board
+    }
+
+    /// Returns the number of moves made so far.
+    pub fn moves(&self) -> u64 {
+        self.moves
+    }
+
+    /// Registers a function to call after each move.
+    /// Hooks are called in the order they were registered.
+    pub fn on_move(&mut self, hook: impl FnMut(&M, Move, u64) + Send + 'static) {
+        self.hooks.push(Box::new(hook));
+    }
+
+    /// Registers a function to call after each move, like [`on_move`](Game::on_move),
+    /// that only receives a [`SpectatorView`] of the board.
+    pub fn watch(
+        &mut self,
+        mut hook: impl FnMut(SpectatorView<'_, M>, Move, u64) + Send + 'static,
+    ) {
+        self.on_move(move |board, action, moves| hook(SpectatorView::new(board), action, moves));
+    }
+
+    /// Saves the game in the given storage according to the policy.
+    ///
+    /// `save` is the game as saved so far, usually a [new save](SavedGame::new) with the seed of the board:
+    /// each move is added to it and the whole save is written under the given id.
+    /// If saving fails, the save is tried again at the next move.
+    pub fn autosave<S: Storage + Send + 'static>(
+        &mut self,
+        storage: S,
+        id: impl Into<String>,
+        save: SavedGame,
+        policy: AutosavePolicy,
+    ) {
+        self.hooks
+            .push(autosave_hook(storage, id.into(), save, policy));
+    }
+
+    /// Returns a read-only view of the board, hiding the closed mines.
+    pub fn spectator(&self) -> SpectatorView<'_, M> {
+        SpectatorView::new(&self.board)
+    }
+
+    /// Makes the given move.
+    pub fn play(&mut self, action: Move) -> Result<()> {
+        match action {
+            Move::Open(coord) => self.open(coord).map(drop),
+            Move::ToggleFlag(coord) => self.toggle_flag(coord).map(drop),
+        }
+    }
+
+    /// [Opens](MineSweeper::open) a cell, counting it as a move.
+    ///
+    /// If the mines are not placed yet and the session has a [seed](GameBuilder::seed),
+    /// they are placed with it as done by [`SavedGame::replay`].
+    ///
+    /// With [auto-finish](GameRules::auto_finish), each cell flagged after the board is cleared
+    /// counts as a [`ToggleFlag`](Move::ToggleFlag) move, made after the opening one.
+    pub fn open(&mut self, coord: Coordinate) -> Result<OpenResult> {
+        if self.is_lost() {
+            return Err(Error::GameOver);
+        }
+        if let Some(seed) = self.seed {
+            place_mines_before(&mut self.board, seed, Move::Open(coord))?;
+        }
+        if !self.rules.chord && self.board.get_cell(coord)?.state == CellState::Open {
+            return Err(Error::AlreadyOpen);
+        }
+        let result = self.board.open(coord)?;
+        self.exploded += result.mines_exploded;
+        let board = &self.board;
+        self.question_marks.retain(
+            |&marked| matches!(board.get_cell(marked), Ok(cell) if cell.state == CellState::Closed),
+        );
+        self.after_move(Move::Open(coord));
+        if self.rules.auto_finish && !self.is_lost() && self.board.is_cleared() {
+            let width = self.board.width();
+            for (i, cell) in self.board.cells_snapshot().into_iter().enumerate() {
+                if cell.state == CellState::Closed {
+                    let coord = (i / width, i % width);
+                    self.question_marks.remove(&coord);
+                    self.board.toggle_flag(coord)?;
+                    self.after_move(Move::ToggleFlag(coord));
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    /// [Toggles the flag](MineSweeper::toggle_flag) on a cell, counting it as a move.
+    ///
+    /// With [question marks](GameRules::question_marks), removing the flag marks the cell
+    /// and toggling a marked cell only removes the mark, returning [`Closed`](CellState::Closed).
+    /// Since removing a mark doesn't change the board, it is not counted as a move nor passed to the hooks.
+    pub fn toggle_flag(&mut self, coord: Coordinate) -> Result<CellState> {
+        if self.is_lost() {
+            return Err(Error::GameOver);
+        }
+        if self.question_marks.remove(&coord) {
+            return Ok(CellState::Closed);
+        }
+        if self.rules.flag_limit
+            && self.board.get_cell(coord)?.state == CellState::Closed
+            && self.board.get_game_state().flagged >= self.board.mines()
+        {
+            return Err(Error::TooManyFlags);
+        }
+        let result = self.board.toggle_flag(coord)?;
+        if self.rules.question_marks && result == CellState::Closed {
+            self.question_marks.insert(coord);
+        }
+        self.after_move(Move::ToggleFlag(coord));
+        Ok(result)
+    }
+
+    fn after_move(&mut self, action: Move) {
+        self.moves += 1;
+        for hook in &mut self.hooks {
+            hook(&self.board, action, self.moves);
+        }
+    }
+}
+
+/// The hook registered by [`Game::autosave`].
+fn autosave_hook<M, S: Storage + Send + 'static>(
+    mut storage: S,
+    id: String,
+    mut save: SavedGame,
+    policy: AutosavePolicy,
+) -> MoveHook<M> {
+    let (mut last_save, mut unsaved) = (Instant::now(), 0);
+    Box::new(move |_, action, _| {
+        save.moves.push(action);
+        unsaved += 1;
+        let due = match policy {
+            AutosavePolicy::EveryMoves(moves) => unsaved >= moves,
+            AutosavePolicy::Every(period) => last_save.elapsed() >= period,
+        };
+        if due && storage.save(&id, &save).is_ok() {
+            (last_save, unsaved) = (Instant::now(), 0);
+        }
+    })
+}
+
+impl<M: MineSweeper + Debug> Debug for Game<M> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Game")
+            .field("board", &self.board)
+            .field("rules", &self.rules)
+            .field("moves", &self.moves)
+            .field("exploded", &self.exploded)
+            .field("question_marks", &self.question_marks)
+            .field("seed", &self.seed)
+            .field("hooks", &self.hooks.len())
+            .finish()
+    }
+}
+
+/// Builds a [`Game`], choosing its [rules](GameRules) one by one and registering its hooks.
+/// The rules that are not chosen are the [default](GameRules::default) ones.
+///
+/// ```
+/// # use mine_sweeperr::{Difficulty, Game, MSHash, MineSweeper, StartPolicy};
+/// let board = MSHash::new(Difficulty::easy(), (4, 4)).unwrap();
+/// let game = Game::builder(board)
+///     .lives(3)
+///     .start(StartPolicy::OpenStart)
+///     .question_marks(true)
+///     .build();
+/// assert_eq!(game.lives_left(), 3);
+/// assert!(game.board().get_game_state().opened > 0);
+/// ```
+pub struct GameBuilder<M: MineSweeper> {
+    board: M,
+    rules: GameRules,
+    seed: Option<u64>,
+    hooks: Vec<MoveHook<M>>,
+}
+
+impl<M: MineSweeper> GameBuilder<M> {
+    /// Starts building a session on the given board.
+    pub fn new(board: M) -> Self {
+        Self {
+            board,
+            rules: GameRules::default(),
+            seed: None,
+            hooks: Vec::new(),
+        }
+    }
+
+    /// Replaces all the rules chosen so far.
+    pub fn rules(mut self, rules: GameRules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Sets [`GameRules::chord`].
+    pub fn chord(mut self, chord: bool) -> Self {
+        self.rules.chord = chord;
+        self
+    }
+
+    /// Sets [`GameRules::flag_limit`].
+    pub fn flag_limit(mut self, flag_limit: bool) -> Self {
+        self.rules.flag_limit = flag_limit;
+        self
+    }
+
+    /// Sets [`GameRules::lives`], which is at least `1`.
+    pub fn lives(mut self, lives: usize) -> Self {
+        self.rules.lives = lives;
+        self
+    }
+
+    /// Sets [`GameRules::start`].
+    pub fn start(mut self, start: StartPolicy) -> Self {
+        self.rules.start = start;
+        self
+    }
+
+    /// Sets [`GameRules::question_marks`].
+    pub fn question_marks(mut self, question_marks: bool) -> Self {
+        self.rules.question_marks = question_marks;
+        self
+    }
+
+    /// Sets [`GameRules::auto_finish`].
+    pub fn auto_finish(mut self, auto_finish: bool) -> Self {
+        self.rules.auto_finish = auto_finish;
+        self
+    }
+
+    /// Places the mines at the first open with a [`Pcg32`](crate::Pcg32) initialized with the given seed,
+    /// if they are not placed yet, so that the session can be [restored](SavedGame::restore)
+    /// from a save with the same seed.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Registers a function to call after each move, like [`Game::on_move`].
+    pub fn on_move(mut self, hook: impl FnMut(&M, Move, u64) + Send + 'static) -> Self {
+        self.hooks.push(Box::new(hook));
+        self
+    }
+
+    /// Saves the game in the given storage according to the policy, like [`Game::autosave`].
+    pub fn autosave<S: Storage + Send + 'static>(
+        mut self,
+        storage: S,
+        id: impl Into<String>,
+        save: SavedGame,
+        policy: AutosavePolicy,
+    ) -> Self {
+        self.hooks
+            .push(autosave_hook(storage, id.into(), save, policy));
+        self
+    }
+
+    /// Starts the session, applying the [start policy](GameRules::start).
+    ///
+    /// With [`StartPolicy::OpenStart`] the starting cell is opened as the first move of the session,
+    /// so it is counted and passed to the hooks registered with the builder.
+    pub fn build(self) -> Game<M> {
+        let mut result = Game {
+            board: self.board,
+            rules: GameRules {
+                lives: self.rules.lives.max(1),
+                ..self.rules
+            },
+            moves: 0,
+            exploded: 0,
+            question_marks: HashSet::new(),
+            seed: self.seed,
+            hooks: self.hooks,
+        };
+        if result.rules.start == StartPolicy::OpenStart && result.board.get_game_state().opened == 0
+        {
+            let start = result
+                .board
+                .started_from()
+                .unwrap_or((result.board.height() / 2, result.board.width() / 2));
+            // the starting cell is in bounds, so this only fails if the game is already lost
+            let _ = result.open(start);
+        }
+        result
+    }
+}
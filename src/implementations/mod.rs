@@ -1,9 +1,25 @@
-///!
+pub use ms_const::{ConstCells, MSConst};
+pub use ms_grid::{GridStorage, MSGrid};
+#[cfg(feature = "std")]
 pub use ms_hash::MSHash;
+#[cfg(feature = "heapless")]
+pub use ms_heapless::{HeaplessCells, MSHeapless};
+#[cfg(feature = "std")]
 pub use ms_matrix::MSMatrix;
+#[cfg(feature = "std")]
+pub use ms_sparse::MSSparse;
 
+mod ms_const;
+mod ms_grid;
+#[cfg(feature = "std")]
 mod ms_hash;
+#[cfg(feature = "heapless")]
+mod ms_heapless;
+#[cfg(feature = "std")]
 mod ms_matrix;
+#[cfg(feature = "std")]
+mod ms_sparse;
+pub mod shared;
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests;
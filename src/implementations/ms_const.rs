@@ -0,0 +1,56 @@
+use crate::{Cell, Error, GridStorage, MSGrid, Result};
+
+/// Represents a grid whose size is known at compile time, stored in an array of `H` rows and `W` columns.
+/// Use this for fixed-size boards, like the [classic](crate::Difficulty::easy) ones:
+/// the whole game lives in a single value, and playing it doesn't allocate.
+///
+/// The [difficulty](crate::Difficulty) given to the constructors must have the same size as the type,
+/// otherwise [`SizeMismatch`](Error::SizeMismatch) is returned.
+///
+/// ```
+/// use mine_sweeperr::{Difficulty, MSConst, MineSweeper};
+///
+/// let mut ms = MSConst::<16, 30>::new_unstarted(Difficulty::hard()).unwrap();
+/// ms.open((0, 0)).unwrap();
+/// assert!(MSConst::<9, 9>::new_unstarted(Difficulty::hard()).is_err());
+/// ```
+pub type MSConst<const H: usize, const W: usize> = MSGrid<ConstCells<H, W>>;
+
+/// The [storage](GridStorage) of [`MSConst`]: an array of `H` rows and `W` columns.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ConstCells<const H: usize, const W: usize>([[Cell; W]; H]);
+
+impl<const H: usize, const W: usize> GridStorage for ConstCells<H, W> {
+    /// Fails with [`SizeMismatch`](Error::SizeMismatch) if the size doesn't match the type.
+    fn with_size(height: usize, width: usize) -> Result<Self> {
+        if (height, width) != (H, W) {
+            return Err(Error::SizeMismatch {
+                height,
+                width,
+                capacity: H * W,
+            });
+        }
+        Ok(Self([[Cell::closed(); W]; H]))
+    }
+
+    fn height(&self) -> usize {
+        H
+    }
+
+    fn width(&self) -> usize {
+        W
+    }
+
+    fn cells(&self) -> &[Cell] {
+        self.0.as_flattened()
+    }
+
+    fn cells_mut(&mut self) -> &mut [Cell] {
+        self.0.as_flattened_mut()
+    }
+
+    /// The stack is an array of the same size, kept on the stack of the program.
+    fn with_stack<T>(_len: usize, f: impl FnOnce(&mut [usize]) -> T) -> T {
+        f([[0; W]; H].as_flattened_mut())
+    }
+}
@@ -0,0 +1,347 @@
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+
+use crate::{
+    is_in_safe_zone, neighbors, shared, validate_difficulty, zobrist_key, Cell, CellContent,
+    CellState, Coordinate, Difficulty, Error, GameState, MineSweeper, NumberRule, OpenResult,
+    Random, Result, StandardNumbers, ValidSetup,
+};
+
+/// Where a [`MSGrid`] keeps its cells, row after row: the cell at `(r, c)` is at index `r * width + c`.
+///
+/// A storage only holds the cells of the board, all the rules of the game are implemented by [`MSGrid`].
+pub trait GridStorage: Sized {
+    /// Creates the storage of a board with the given size, with all the cells closed and without mines.
+    ///
+    /// # Suggested Errors
+    /// - [`SizeMismatch`](Error::SizeMismatch) if the storage can't hold a board of this size.
+    fn with_size(height: usize, width: usize) -> Result<Self>;
+    fn height(&self) -> usize;
+    fn width(&self) -> usize;
+    /// Returns all the cells, row after row.
+    fn cells(&self) -> &[Cell];
+    /// Returns all the cells, row after row, to change them.
+    fn cells_mut(&mut self) -> &mut [Cell];
+    /// Returns the indexes of the neighbors of the cell at the given index (excluded the cell itself).
+    /// Storages can override it if they know the neighbors faster than computing their coordinates.
+    fn neighbors(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        let width = self.width();
+        neighbors((index / width, index % width), self.height(), width)
+            .map(move |(r, c)| r * width + c)
+    }
+    /// Calls `f` with room for `len` indexes, `len` being the number of cells of the storage.
+    /// It is the stack used to open cells without allocating, so it should be kept where the cells are.
+    fn with_stack<T>(len: usize, f: impl FnOnce(&mut [usize]) -> T) -> T;
+}
+
+/// Represents a grid whose cells are kept in a [`GridStorage`].
+/// Each storage gives a different implementation, like [`MSConst`](crate::MSConst),
+/// while the rules, the counters and the [position hash](MineSweeper::position_hash) are the same for all of them.
+///
+/// Opening cells doesn't allocate, since the cells to expand are kept in the
+/// [stack](GridStorage::with_stack) of the storage.
+/// Only [`cells_snapshot`](MineSweeper::cells_snapshot) and
+/// [`apply_number_rule`](MineSweeper::apply_number_rule), which reads a snapshot, allocate.
+///
+/// # Solver
+/// The mines are placed at random, without verifying the board with a [solver](crate::solver::Solver).
+/// Use [`MSMatrix`](crate::MSMatrix) to generate boards that a solver can solve.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MSGrid<S> {
+    storage: S,
+    mines: usize,
+    start_from: Option<Coordinate>,
+    opened: usize,
+    flagged: usize,
+    exploded: usize,
+    /// [`NumberRule::OPENS_NEIGHBORS`] of the rule that computed the numbers.
+    opens_neighbors: bool,
+    /// The [position hash](MineSweeper::position_hash), updated with each move.
+    position_hash: u64,
+}
+
+impl<S: GridStorage> MSGrid<S> {
+    /// Creates an empty board, checking only that the storage can hold it.
+    fn new_unchecked(difficulty: Difficulty) -> Result<Self> {
+        let (height, width, mines) = difficulty.into();
+        Ok(Self::from_storage(
+            S::with_size(height, width)?,
+            mines,
+            None,
+        ))
+    }
+
+    /// Creates a board with the given storage, whose cells must all be closed.
+    /// Used by the implementations that place the mines themselves, like [`MSMatrix`](crate::MSMatrix).
+    pub(crate) fn from_storage(storage: S, mines: usize, start_from: Option<Coordinate>) -> Self {
+        Self {
+            storage,
+            mines,
+            start_from,
+            opened: 0,
+            flagged: 0,
+            exploded: 0,
+            opens_neighbors: StandardNumbers::OPENS_NEIGHBORS,
+            position_hash: 0,
+        }
+    }
+
+    /// Returns the storage of the cells.
+    pub fn storage(&self) -> &S {
+        &self.storage
+    }
+
+    #[cfg(feature = "std")]
+    /// Returns the storage of the cells to change their content,
+    /// which doesn't change the position hash as long as they are closed.
+    pub(crate) fn storage_mut(&mut self) -> &mut S {
+        &mut self.storage
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn set_opens_neighbors(&mut self, opens_neighbors: bool) {
+        self.opens_neighbors = opens_neighbors;
+    }
+
+    #[cfg(feature = "std")]
+    /// Copies the state of the cells and the counters of another board with the same size,
+    /// recomputing the position hash with the content of the cells of this board.
+    pub(crate) fn copy_states(&mut self, other: &Self) {
+        for (cell, previous) in self
+            .storage
+            .cells_mut()
+            .iter_mut()
+            .zip(other.storage.cells())
+        {
+            cell.state = previous.state;
+        }
+        (self.opened, self.flagged, self.exploded) = (other.opened, other.flagged, other.exploded);
+        self.position_hash = crate::compute_position_hash(self);
+    }
+
+    fn index(&self, (r, c): Coordinate) -> usize {
+        r * self.storage.width() + c
+    }
+
+    /// Randomizes the positions of mines when initializing the board.
+    fn randomize_mines(&mut self, start_from: Coordinate, rng: &mut impl Random) {
+        let (height, width) = (self.storage.height(), self.storage.width());
+        let mut mines_left = self.mines;
+        while mines_left > 0 {
+            let coord = (rng.below(height), rng.below(width));
+            let index = self.index(coord);
+            if let CellContent::Number(_) = self.storage.cells()[index].content {
+                if !is_in_safe_zone(coord, start_from) {
+                    self.storage.cells_mut()[index].content = CellContent::Mine;
+                    for neighbor in self.neighbors(index) {
+                        let cell = &mut self.storage.cells_mut()[neighbor];
+                        if let CellContent::Number(n) = cell.content {
+                            cell.content = CellContent::Number(n + 1);
+                        }
+                    }
+                    mines_left -= 1;
+                }
+            }
+        }
+    }
+
+    /// Returns the indexes of the neighbors of a cell given by the storage,
+    /// copied so that the cells can be changed while visiting them.
+    fn neighbors(&self, index: usize) -> impl Iterator<Item = usize> {
+        let (mut neighbors, mut len) = ([0; 8], 0);
+        for neighbor in self.storage.neighbors(index) {
+            neighbors[len] = neighbor;
+            len += 1;
+        }
+        neighbors.into_iter().take(len)
+    }
+
+    /// Changes the state of a cell, as required by the functions in [`shared`],
+    /// and updates the counters and the position hash.
+    fn set_state(&mut self, coord: Coordinate, state: CellState) {
+        let index = self.index(coord);
+        let cell = &mut self.storage.cells_mut()[index];
+        let before = *cell;
+        cell.state = state;
+        let after = *cell;
+        match (before.state, state) {
+            (_, CellState::Open) => {
+                self.opened += 1;
+                if before.content == CellContent::Mine {
+                    self.exploded += 1;
+                }
+            }
+            (_, CellState::Flagged) => self.flagged += 1,
+            (CellState::Flagged, CellState::Closed) => self.flagged -= 1,
+            _ => {}
+        }
+        self.position_hash ^= zobrist_key(coord, before) ^ zobrist_key(coord, after);
+    }
+
+    /// Checks the validity of a coordinate.
+    fn check_coordinate(&self, coord @ (r, c): Coordinate) -> Result<()> {
+        let (height, width) = (self.storage.height(), self.storage.width());
+        if r < height && c < width {
+            Ok(())
+        } else {
+            Err(Error::OutOfBounds {
+                coord,
+                height,
+                width,
+            })
+        }
+    }
+
+    /// Opens the cell at the given index, that must be closed, and returns whether it was a mine.
+    fn open_closed(&mut self, index: usize) -> bool {
+        let width = self.storage.width();
+        self.set_state((index / width, index % width), CellState::Open);
+        self.storage.cells()[index].content == CellContent::Mine
+    }
+}
+
+impl<S: GridStorage> MineSweeper for MSGrid<S> {
+    /// Creates a new game without checking the setup again,
+    /// except for the size of the board that must fit in the storage.
+    fn from_setup(setup: ValidSetup, rng: &mut impl Random) -> Result<Self> {
+        let mut result = Self::new_unchecked(setup.difficulty())?;
+        result.randomize_mines(setup.start_from(), rng);
+        result.start_from = Some(setup.start_from());
+        Ok(result)
+    }
+
+    fn new_unstarted(difficulty: Difficulty) -> Result<Self> {
+        validate_difficulty(difficulty)?;
+        Self::new_unchecked(difficulty)
+    }
+
+    fn place_mines_from_rng(
+        &mut self,
+        start_from: Coordinate,
+        rng: &mut impl Random,
+    ) -> Result<()> {
+        if self.start_from.is_some() {
+            return Err(Error::AlreadyStarted);
+        }
+        self.check_coordinate(start_from)?;
+        // all the cells are closed or flagged, so their content doesn't change the hash
+        self.randomize_mines(start_from, rng);
+        self.start_from = Some(start_from);
+        Ok(())
+    }
+
+    fn apply_number_rule<R: NumberRule>(&mut self, rule: &mut R) -> Result<()> {
+        shared::apply_number_rule(self, rule, |ms, coord, number| {
+            let index = ms.index(coord);
+            ms.storage.cells_mut()[index].content = CellContent::Number(number);
+        })?;
+        self.opens_neighbors = R::OPENS_NEIGHBORS;
+        Ok(())
+    }
+
+    fn opens_neighbors(&self, number: u8, flags: u8) -> bool {
+        self.opens_neighbors && flags >= number
+    }
+
+    /// Implements all the additional rules suggested in the [trait interface](MineSweeper::open),
+    /// like [`flood_open`](crate::flood_open).
+    ///
+    /// The cells to expand are kept in the [stack](GridStorage::with_stack) of the storage.
+    /// Each cell is pushed when it is opened, so it is expanded only once and the stack never overflows.
+    fn open(&mut self, coord: Coordinate) -> Result<OpenResult> {
+        if self.start_from.is_none() {
+            self.place_mines(coord)?;
+        }
+        self.check_coordinate(coord)?;
+        let (height, width) = (self.storage.height(), self.storage.width());
+        let start = self.index(coord);
+        let (mut cells_opened, mut mines_exploded) = (0, 0);
+        S::with_stack(height * width, |stack| {
+            let mut len = 0;
+            match self.storage.cells()[start].state {
+                CellState::Flagged => {}
+                CellState::Closed => {
+                    cells_opened += 1;
+                    mines_exploded += self.open_closed(start) as usize;
+                    (stack[0], len) = (start, 1);
+                }
+                CellState::Open => (stack[0], len) = (start, 1),
+            }
+            while len > 0 {
+                len -= 1;
+                let index = stack[len];
+                let CellContent::Number(number) = self.storage.cells()[index].content else {
+                    continue;
+                };
+                let flags = self
+                    .storage
+                    .neighbors(index)
+                    .filter(|&neighbor| self.storage.cells()[neighbor].state == CellState::Flagged)
+                    .count() as u8;
+                if self.opens_neighbors(number, flags) {
+                    for neighbor in self.neighbors(index) {
+                        if self.storage.cells()[neighbor].state == CellState::Closed {
+                            cells_opened += 1;
+                            mines_exploded += self.open_closed(neighbor) as usize;
+                            stack[len] = neighbor;
+                            len += 1;
+                        }
+                    }
+                }
+            }
+        });
+        Ok(OpenResult::new(
+            self.storage.cells()[start],
+            cells_opened,
+            mines_exploded,
+        ))
+    }
+
+    fn open_one(&mut self, coord: Coordinate) -> Result<CellContent> {
+        shared::open_one(self, coord, Self::set_state)
+    }
+
+    fn toggle_flag(&mut self, coord: Coordinate) -> Result<CellState> {
+        shared::toggle_flag(self, coord, Self::set_state)
+    }
+
+    fn get_cell(&self, coord: Coordinate) -> Result<Cell> {
+        self.check_coordinate(coord)?;
+        Ok(self.storage.cells()[self.index(coord)])
+    }
+
+    /// Returns a copy of the whole grid, already stored in row-major order.
+    fn cells_snapshot(&self) -> Vec<Cell> {
+        self.storage.cells().to_vec()
+    }
+
+    fn height(&self) -> usize {
+        self.storage.height()
+    }
+
+    fn width(&self) -> usize {
+        self.storage.width()
+    }
+
+    fn mines(&self) -> usize {
+        self.mines
+    }
+
+    fn started_from(&self) -> Option<Coordinate> {
+        self.start_from
+    }
+
+    fn position_hash(&self) -> u64 {
+        self.position_hash
+    }
+
+    fn get_game_state(&self) -> GameState {
+        GameState::new(self.mines, self.opened, self.flagged, self.exploded)
+    }
+}
+
+impl<S: GridStorage> Display for MSGrid<S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        MineSweeper::fmt(self, f)
+    }
+}
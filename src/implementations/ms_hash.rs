@@ -1,13 +1,13 @@
 use std::{
-    collections::{HashSet, VecDeque},
+    collections::{HashMap, HashSet},
     fmt::{Display, Formatter},
+    mem::size_of,
 };
 
-use rand::Rng;
-
 use crate::{
-    check, count_neighboring_flags, iter_neighbors, Cell, CellContent, CellState, Coordinate,
-    Difficulty, Error, GameState, MineSweeper, OpenResult, Result,
+    is_in_safe_zone, neighbors, shared, validate_difficulty, zobrist_key, Cell, CellContent,
+    CellState, Coordinate, Difficulty, Error, GameState, MineSweeper, NumberRule, OpenResult,
+    Random, Result, StandardNumbers, ValidSetup,
 };
 
 /// Represents a grid using [`HashSets`](HashSet) of [`Coordinates`](Coordinate).
@@ -24,141 +24,173 @@ pub struct MSHash {
     open: HashSet<Coordinate>,
     flagged: HashSet<Coordinate>,
     mines: HashSet<Coordinate>,
-    start_from: Coordinate,
+    /// The number of mines, also before they are placed.
+    total_mines: usize,
+    start_from: Option<Coordinate>,
     exploded: usize,
+    /// The numbers computed by a [`NumberRule`] that differ from the count of the mines around the cell.
+    numbers: HashMap<Coordinate, u8>,
+    /// [`NumberRule::OPENS_NEIGHBORS`] of the rule that computed the numbers.
+    opens_neighbors: bool,
+    /// The [position hash](MineSweeper::position_hash), updated with each move.
+    position_hash: u64,
 }
 
 impl MSHash {
     /// Creates a new instance.
-    fn new_unchecked(height: usize, width: usize, mines: usize, start_from: Coordinate) -> Self {
+    fn new_unchecked(height: usize, width: usize, mines: usize) -> Self {
         Self {
             height,
             width,
             open: Default::default(),
             flagged: Default::default(),
             mines: HashSet::with_capacity(mines),
-            start_from,
+            total_mines: mines,
+            start_from: None,
             exploded: 0,
+            numbers: HashMap::new(),
+            opens_neighbors: StandardNumbers::OPENS_NEIGHBORS,
+            position_hash: 0,
         }
     }
 
     /// Randomizes the positions of mines when initializing the board.
-    fn randomize_mines(&mut self, mines: usize, start_from: Coordinate, rng: &mut impl Rng) {
-        let mut must_be_safe = iter_neighbors(start_from, self.height, self.width)
-            .unwrap()
-            .collect::<Vec<_>>();
-        must_be_safe.push(start_from);
+    fn randomize_mines(&mut self, mines: usize, start_from: Coordinate, rng: &mut impl Random) {
         while self.mines.len() < mines {
-            let coord = (rng.gen_range(0..self.height), rng.gen_range(0..self.width));
-            if !self.mines.contains(&coord) && !must_be_safe.contains(&coord) {
+            let coord = (rng.below(self.height), rng.below(self.width));
+            if !self.mines.contains(&coord) && !is_in_safe_zone(coord, start_from) {
                 self.mines.insert(coord);
             }
         }
     }
 
+    /// Changes the state of a cell, as required by the functions in [`shared`],
+    /// and updates the position hash.
+    fn set_state(&mut self, coord: Coordinate, state: CellState) {
+        let before = self.get_cell(coord).unwrap();
+        match state {
+            CellState::Open => {
+                self.open.insert(coord);
+                if self.mines.contains(&coord) {
+                    self.exploded += 1;
+                }
+            }
+            CellState::Flagged => {
+                self.flagged.insert(coord);
+            }
+            CellState::Closed => {
+                self.flagged.remove(&coord);
+            }
+        }
+        self.position_hash ^=
+            zobrist_key(coord, before) ^ zobrist_key(coord, self.get_cell(coord).unwrap());
+    }
+
     /// Checks the validity of a coordinate.
-    fn check_coordinate(&self, (r, c): Coordinate) -> Result<()> {
+    fn check_coordinate(&self, coord @ (r, c): Coordinate) -> Result<()> {
         if r < self.height && c < self.width {
             Ok(())
         } else {
-            Err(Error::OutOfBounds)
+            Err(Error::OutOfBounds {
+                coord,
+                height: self.height,
+                width: self.width,
+            })
         }
     }
 
+    /// Returns an estimate of the memory used by this instance, in bytes.
+    ///
+    /// The estimate includes the struct itself, the allocated capacity of the three sets
+    /// and of the numbers changed by a [`NumberRule`],
+    /// counting one extra control byte per slot as done by the standard [`HashSet`](HashSet).
+    pub fn memory_footprint(&self) -> usize {
+        let slot = size_of::<Coordinate>() + 1;
+        size_of::<Self>()
+            + (self.open.capacity() + self.flagged.capacity() + self.mines.capacity()) * slot
+            + self.numbers.capacity() * (slot + size_of::<u8>())
+    }
+
     /// Counts the number of mines around a cell.
     fn count_neighboring_mines(&self, coord: Coordinate) -> u8 {
-        iter_neighbors(coord, self.height, self.width)
-            .unwrap()
+        neighbors(coord, self.height, self.width)
             .filter(|coord| self.mines.contains(coord))
             .count() as u8
     }
 }
 
 impl MineSweeper for MSHash {
-    /// Creates a new game performing all the suggested checks.
-    fn from_rng(
-        difficulty: Difficulty,
-        start_from: Coordinate,
-        rng: &mut impl Rng,
-    ) -> Result<Self> {
-        let difficulty @ (height, width, mines) = difficulty.into();
-        check!(difficulty, start_from);
-        let mut result = Self::new_unchecked(height, width, mines, start_from);
-        result.randomize_mines(mines, start_from, rng);
+    /// Creates a new game without checking the setup again.
+    fn from_setup(setup: ValidSetup, rng: &mut impl Random) -> Result<Self> {
+        let (height, width, mines) = setup.difficulty().into();
+        let mut result = Self::new_unchecked(height, width, mines);
+        result.randomize_mines(mines, setup.start_from(), rng);
+        result.start_from = Some(setup.start_from());
         Ok(result)
     }
 
+    fn new_unstarted(difficulty: Difficulty) -> Result<Self> {
+        validate_difficulty(difficulty)?;
+        let (height, width, mines) = difficulty.into();
+        Ok(Self::new_unchecked(height, width, mines))
+    }
+
+    fn place_mines_from_rng(
+        &mut self,
+        start_from: Coordinate,
+        rng: &mut impl Random,
+    ) -> Result<()> {
+        if self.start_from.is_some() {
+            return Err(Error::AlreadyStarted);
+        }
+        self.check_coordinate(start_from)?;
+        self.randomize_mines(self.total_mines, start_from, rng);
+        self.start_from = Some(start_from);
+        Ok(())
+    }
+
+    fn apply_number_rule<R: NumberRule>(&mut self, rule: &mut R) -> Result<()> {
+        shared::apply_number_rule(self, rule, |ms, coord, number| {
+            if number == ms.count_neighboring_mines(coord) {
+                ms.numbers.remove(&coord);
+            } else {
+                ms.numbers.insert(coord, number);
+            }
+        })?;
+        self.opens_neighbors = R::OPENS_NEIGHBORS;
+        Ok(())
+    }
+
+    fn opens_neighbors(&self, number: u8, flags: u8) -> bool {
+        self.opens_neighbors && flags >= number
+    }
+
     /// Implements all the additional rules suggested in the [trait interface](MineSweeper::open).
     ///
-    /// The opening procedure is made using a [queue](VecDeque) (not recursive).
+    /// The opening procedure is delegated to [`shared::open`].
     ///
     /// Due to the implementation with [`HashSet`](HashSet), this method may be quite slow for large grids.
     /// For better speed performances, use [`MSMatrix`](crate::MSMatrix).
     fn open(&mut self, coord: Coordinate) -> Result<OpenResult> {
-        self.check_coordinate(coord)?;
-        let (mut cells_opened, mut mines_exploded) = (0, 0);
-        let mut queue = VecDeque::from([coord]);
-        let mut cell: Cell;
-        while !queue.is_empty() {
-            let coord = queue.pop_front().unwrap();
-            cell = self.get_cell(coord).unwrap();
-            if cell.state != CellState::Flagged {
-                if cell.state == CellState::Closed {
-                    self.open.insert(coord);
-                    cells_opened += 1;
-                    if cell.content == CellContent::Mine {
-                        mines_exploded += 1;
-                    }
-                }
-                if let CellContent::Number(neighboring_mines) = cell.content {
-                    if count_neighboring_flags(self, coord) >= neighboring_mines {
-                        iter_neighbors(coord, self.height, self.width)
-                            .unwrap()
-                            .filter(|&coord| self.get_cell(coord).unwrap().state != CellState::Open)
-                            .for_each(|coord| queue.push_back(coord));
-                    }
-                }
-            }
-        }
-        self.exploded += mines_exploded;
-        Ok(OpenResult::new(
-            self.get_cell(coord).unwrap(),
-            cells_opened,
-            mines_exploded,
-        ))
+        shared::open(self, coord, Self::set_state)
     }
 
     fn open_one(&mut self, coord: Coordinate) -> Result<CellContent> {
-        self.check_coordinate(coord)?;
-        let cell = self.get_cell(coord).unwrap();
-        if cell.state == CellState::Closed {
-            self.open.insert(coord);
-            if cell.content == CellContent::Mine {
-                self.exploded += 1;
-            }
-        }
-        Ok(cell.content)
+        shared::open_one(self, coord, Self::set_state)
     }
 
     fn toggle_flag(&mut self, coord: Coordinate) -> Result<CellState> {
-        self.check_coordinate(coord)?;
-        if self.open.contains(&coord) {
-            return Err(Error::AlreadyOpen);
-        }
-        if self.flagged.contains(&coord) {
-            self.flagged.remove(&coord);
-            Ok(CellState::Flagged)
-        } else {
-            self.flagged.insert(coord);
-            Ok(CellState::Flagged)
-        }
+        shared::toggle_flag(self, coord, Self::set_state)
     }
 
     fn get_cell(&self, coord: Coordinate) -> Result<Cell> {
         self.check_coordinate(coord)?;
         let (mut state, mut content) = (CellState::Closed, CellContent::Mine);
         if !self.mines.contains(&coord) {
-            content = CellContent::Number(self.count_neighboring_mines(coord));
+            content = CellContent::Number(match self.numbers.get(&coord) {
+                Some(&number) => number,
+                None => self.count_neighboring_mines(coord),
+            });
         }
         if self.open.contains(&coord) {
             state = CellState::Open;
@@ -177,19 +209,24 @@ impl MineSweeper for MSHash {
     }
 
     fn mines(&self) -> usize {
-        self.mines.len()
+        self.total_mines
     }
 
-    fn started_from(&self) -> Coordinate {
+    fn started_from(&self) -> Option<Coordinate> {
         self.start_from
     }
 
+    fn position_hash(&self) -> u64 {
+        self.position_hash
+    }
+
     fn get_game_state(&self) -> GameState {
-        GameState {
-            opened: self.open.len(),
-            flagged: self.flagged.len(),
-            mines_left: self.mines.len() - self.flagged.len() - self.exploded,
-        }
+        GameState::new(
+            self.total_mines,
+            self.open.len(),
+            self.flagged.len(),
+            self.exploded,
+        )
     }
 }
 
@@ -0,0 +1,63 @@
+use heapless::Vec;
+
+use crate::{Cell, Error, GridStorage, MSGrid, Result};
+
+/// Represents a grid stored in a [`heapless::Vec`] with room for at most `N` cells,
+/// available with the `heapless` feature.
+/// Use this on embedded targets, where the memory for the board must be reserved at compile time
+/// but its size is chosen at runtime: playing it doesn't allocate.
+///
+/// Boards with more than `N` cells are rejected by the constructors with
+/// [`SizeMismatch`](Error::SizeMismatch).
+pub type MSHeapless<const N: usize> = MSGrid<HeaplessCells<N>>;
+
+/// The [storage](GridStorage) of [`MSHeapless`]: a [`heapless::Vec`] with room for at most `N` cells.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HeaplessCells<const N: usize> {
+    height: usize,
+    width: usize,
+    cells: Vec<Cell, N>,
+}
+
+impl<const N: usize> GridStorage for HeaplessCells<N> {
+    /// Fails with [`SizeMismatch`](Error::SizeMismatch) if the cells don't fit in the capacity.
+    fn with_size(height: usize, width: usize) -> Result<Self> {
+        let mut cells = Vec::new();
+        cells
+            .resize(height * width, Cell::closed())
+            .map_err(|()| Error::SizeMismatch {
+                height,
+                width,
+                capacity: N,
+            })?;
+        Ok(Self {
+            height,
+            width,
+            cells,
+        })
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn cells(&self) -> &[Cell] {
+        &self.cells
+    }
+
+    fn cells_mut(&mut self) -> &mut [Cell] {
+        &mut self.cells
+    }
+
+    /// The stack is a [`heapless::Vec`] with the same capacity, kept on the stack of the program.
+    fn with_stack<T>(len: usize, f: impl FnOnce(&mut [usize]) -> T) -> T {
+        let mut stack = Vec::<usize, N>::new();
+        // the cells fit in the capacity, so their indexes do too
+        stack.resize(len, 0).unwrap();
+        f(&mut stack)
+    }
+}
@@ -1,96 +1,411 @@
 use std::{
-    collections::VecDeque,
+    collections::HashSet,
     fmt::{Display, Formatter},
+    hash::{Hash, Hasher},
+    mem::size_of,
 };
 
-use rand::{seq::SliceRandom, Rng};
-
 use crate::{
-    check, count_neighboring_flags, count_neighboring_mines, iter_neighbors, solver,
+    default_random, is_in_safe_zone, shared,
     solver::{NonDeterministic, Solver},
-    Cell, CellContent, CellState, Coordinate, Difficulty, Error, GameState, MineSweeper,
-    OpenResult, Result,
+    validate_difficulty, validate_setup, Cell, CellContent, CellState, Coordinate, Difficulty,
+    Error, GameState, GridStorage, MSGrid, MineSweeper, NumberRule, OpenResult, Pcg32, Random,
+    Result, StandardNumbers, ValidSetup,
 };
 
-// const MAX_SHUFFLE: usize = 10;
+/// The maximum number of times the unsolvable clusters of a board are shuffled
+/// before discarding the whole board during generation.
+const MAX_SHUFFLE: usize = 10;
+
+/// Places the mines of a board with the solver chosen at construction.
+type MinePlacer = fn(&mut MSMatrix, Coordinate, &mut dyn Random) -> Result<()>;
+
+/// Lets a [`dyn Random`](Random) be passed where a sized generator is expected.
+struct DynRandom<'a>(&'a mut dyn Random);
+
+impl Random for DynRandom<'_> {
+    fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        self.0.below(n)
+    }
+}
 
 /// Represents the grid using a matrix of [`cells`](Cell).
 /// Use this when you want to load the whole grid in memory at the beginning.
 /// Has better performances when opening cells but takes more memory.
 ///
+/// The game is played on an [`MSGrid`] whose cells are stored in a single flat vector, row after row.
+/// The relative positions of the neighbors of each cell are precomputed at construction,
+/// so visiting the neighbors of a cell is a simple walk over a slice of offsets.
+///
 /// # Solver
 /// This implementation supports passing a [`Solver`](Solver)
 /// to both the constructors. if you use the trait constructors
 /// ([`new`](MineSweeper::new) and [`from_rng`](MineSweeper::from_rng))
 /// to create an instance of this struct,
 /// the [default solver](NonDeterministic) will be used.
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// Boards created with [`new_unstarted`](MSMatrix::new_unstarted)
+/// remember their solver until the mines are placed.
+///
+/// When the solver fails, the board is not discarded immediately:
+/// mines are shuffled inside the [unsolvable clusters](Solver::get_unsolvable_clusters)
+/// reported by the solver and the board is verified again.
+/// Only if this fails too many times a new board is generated from scratch.
+#[derive(Debug, Clone)]
 pub struct MSMatrix {
+    grid: MSGrid<MatrixCells>,
+    /// Used when the mines are placed after the construction of the board.
+    placer: MinePlacer,
+    /// Whether each number is the count of the mines around the cell,
+    /// returned by [`has_standard_numbers`](MineSweeper::has_standard_numbers).
+    standard_numbers: bool,
+    seed: u64,
+}
+
+/// The [storage](GridStorage) of [`MSMatrix`], with the table of the neighbors of its cells.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct MatrixCells {
+    cells: Vec<Cell>,
+    neighbors: NeighborTable,
+}
+
+impl GridStorage for MatrixCells {
+    fn with_size(height: usize, width: usize) -> Result<Self> {
+        Ok(Self {
+            cells: vec![Cell::default(); height * width],
+            neighbors: NeighborTable::new(height, width),
+        })
+    }
+
+    fn height(&self) -> usize {
+        self.neighbors.height
+    }
+
+    fn width(&self) -> usize {
+        self.neighbors.width
+    }
+
+    fn cells(&self) -> &[Cell] {
+        &self.cells
+    }
+
+    fn cells_mut(&mut self) -> &mut [Cell] {
+        &mut self.cells
+    }
+
+    fn neighbors(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        self.neighbors.of(index)
+    }
+
+    /// The stack is allocated on the heap, like the cells.
+    fn with_stack<T>(len: usize, f: impl FnOnce(&mut [usize]) -> T) -> T {
+        f(&mut vec![0; len])
+    }
+}
+
+/// Offsets from the index of a cell to the indexes of its neighbors in a flat grid.
+///
+/// All the cells on the same kind of row (first, last, middle or the only one)
+/// and on the same kind of column share the same offsets,
+/// so the table has 16 entries regardless of the size of the grid.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct NeighborTable {
     height: usize,
     width: usize,
-    mines: usize,
-    cells: Vec<Vec<Cell>>,
-    start_from: Coordinate,
-    opened: usize,
-    flagged: usize,
-    exploded: usize,
-    seed: u64,
+    offsets: Vec<Vec<isize>>,
+}
+
+impl NeighborTable {
+    fn new(height: usize, width: usize) -> Self {
+        // Allowed steps for each kind of row or column: only, first, middle, last.
+        const STEPS: [&[isize]; 4] = [&[], &[1], &[-1, 1], &[-1]];
+        let mut offsets = Vec::with_capacity(16);
+        for row_steps in STEPS {
+            for column_steps in STEPS {
+                let mut cell_offsets = Vec::with_capacity(8);
+                for &dr in row_steps.iter().chain(&[0]) {
+                    for &dc in column_steps.iter().chain(&[0]) {
+                        if (dr, dc) != (0, 0) {
+                            cell_offsets.push(dr * width as isize + dc);
+                        }
+                    }
+                }
+                offsets.push(cell_offsets);
+            }
+        }
+        Self {
+            height,
+            width,
+            offsets,
+        }
+    }
+
+    fn kind(i: usize, len: usize) -> usize {
+        match (i == 0, i == len - 1) {
+            (true, true) => 0,
+            (true, false) => 1,
+            (false, false) => 2,
+            (false, true) => 3,
+        }
+    }
+
+    /// Returns the indexes of the neighbors of the cell at the given index (excluded the cell itself).
+    fn of(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        let kind = Self::kind(index / self.width, self.height) * 4
+            + Self::kind(index % self.width, self.width);
+        self.offsets[kind]
+            .iter()
+            .map(move |&offset| index.wrapping_add_signed(offset))
+    }
 }
 
 impl MSMatrix {
-    /// Creates a new instance of the game with the given solver and the default rng ([`thread_rng`](rand::thread_rng)).
+    /// Creates a new instance of the game with the given solver and the [default rng](MineSweeper::new).
     pub fn new<S: Solver<Self>>(difficulty: Difficulty, start_from: Coordinate) -> Result<Self> {
-        Self::from_rng::<S>(difficulty, start_from, &mut rand::thread_rng())
+        Self::from_rng::<S>(difficulty, start_from, &mut default_random())
+    }
+
+    /// Creates a new instance of the game with the given solver and a [`Pcg32`](crate::Pcg32) with the given seed.
+    pub fn from_seed<S: Solver<Self>>(
+        difficulty: Difficulty,
+        start_from: Coordinate,
+        seed: u64,
+    ) -> Result<Self> {
+        Self::from_rng::<S>(difficulty, start_from, &mut Pcg32::new(seed))
     }
 
     /// Creates a new instance of the game with the given solver and the given rng.
     pub fn from_rng<S: Solver<Self>>(
         difficulty: Difficulty,
         start_from: Coordinate,
-        rng: &mut impl Rng,
+        rng: &mut impl Random,
     ) -> Result<Self> {
-        let difficulty @ (height, width, mines) = difficulty.into();
-        check!(difficulty, start_from);
-        let mut result;
+        Self::from_setup::<S>(validate_setup(difficulty, start_from)?, rng)
+    }
+
+    /// Creates a new instance of the game with the given solver and the given rng,
+    /// from a setup that was already checked by [`validate_setup`].
+    pub fn from_setup<S: Solver<Self>>(setup: ValidSetup, rng: &mut impl Random) -> Result<Self> {
+        Self::from_setup_with_rule::<S, _>(setup, &mut StandardNumbers, rng)
+    }
+
+    /// Creates a new instance of the game like [`from_setup`](MSMatrix::from_setup),
+    /// with the numbers computed by the given rule.
+    /// The solver verifies the board with the standard numbers,
+    /// then the rule is applied once to the accepted board, unless it is [standard](NumberRule::STANDARD).
+    ///
+    /// If the rule changes the [standard numbers](crate::MineSweeper::has_standard_numbers)
+    /// and the solver doesn't [support it](Solver::supports_number_rules),
+    /// returns [`UnsupportedNumberRule`](Error::UnsupportedNumberRule), since the solver can't play the board.
+    /// The rule is first tried on a random board, so this is found out before running the solver.
+    pub fn from_setup_with_rule<S: Solver<Self>, R: NumberRule>(
+        setup: ValidSetup,
+        rule: &mut R,
+        rng: &mut impl Random,
+    ) -> Result<Self> {
+        let (height, width, mines) = setup.difficulty().into();
+        let mut result =
+            Self::generate::<S, R>(height, width, mines, setup.start_from(), rule, rng)?;
+        result.placer = Self::place_mines_dyn::<S>;
+        Ok(result)
+    }
+
+    /// Creates a new instance of the game whose mines are not placed yet,
+    /// as in [`MineSweeper::new_unstarted`].
+    /// The given solver is used when the mines are placed by the first call to [`open`](MineSweeper::open).
+    pub fn new_unstarted<S: Solver<Self>>(difficulty: Difficulty) -> Result<Self> {
+        validate_difficulty(difficulty)?;
+        let (height, width, mines) = difficulty.into();
+        let mut result = Self::new_unchecked(height, width, mines, None);
+        result.placer = Self::place_mines_dyn::<S>;
+        Ok(result)
+    }
+
+    /// Places the mines on a board created with [`new_unstarted`](MineSweeper::new_unstarted),
+    /// with the given solver and the [default rng](MineSweeper::new).
+    pub fn place_mines<S: Solver<Self>>(&mut self, start_from: Coordinate) -> Result<()> {
+        self.place_mines_from_rng::<S>(start_from, &mut default_random())
+    }
+
+    /// Places the mines on a board created with [`new_unstarted`](MineSweeper::new_unstarted),
+    /// with the given solver and the given rng. Flags placed before are kept.
+    pub fn place_mines_from_rng<S: Solver<Self>>(
+        &mut self,
+        start_from: Coordinate,
+        rng: &mut impl Random,
+    ) -> Result<()> {
+        if self.grid.started_from().is_some() {
+            return Err(Error::AlreadyStarted);
+        }
+        self.grid.get_cell(start_from)?;
+        // The board is verified without flags, so that the solver doesn't rely on them.
+        let mut result = Self::generate::<S, _>(
+            self.grid.height(),
+            self.grid.width(),
+            self.grid.mines(),
+            start_from,
+            &mut StandardNumbers,
+            rng,
+        )?;
+        result.grid.copy_states(&self.grid);
+        result.placer = self.placer;
+        *self = result;
+        Ok(())
+    }
+
+    fn place_mines_dyn<S: Solver<Self>>(
+        &mut self,
+        start_from: Coordinate,
+        rng: &mut dyn Random,
+    ) -> Result<()> {
+        self.place_mines_from_rng::<S>(start_from, &mut DynRandom(rng))
+    }
+
+    /// Generates boards until one is accepted by the given solver,
+    /// then applies the rule to it.
+    fn generate<S: Solver<Self>, R: NumberRule>(
+        height: usize,
+        width: usize,
+        mines: usize,
+        start_from: Coordinate,
+        rule: &mut R,
+        rng: &mut impl Random,
+    ) -> Result<Self> {
+        let mut result = Self::new_unchecked(height, width, mines, Some(start_from));
+        result.randomize_mines(mines, start_from, rng);
+        if !R::STANDARD && !S::supports_number_rules() {
+            // find out whether the rule changes the numbers before running the solver
+            let mut applied = result.clone();
+            applied.apply_number_rule(rule)?;
+            if !applied.has_standard_numbers() {
+                return Err(Error::UnsupportedNumberRule);
+            }
+        }
+        let mut shuffles = 0;
         loop {
-            result = Self::new_unchecked(height, width, mines, start_from);
-            result.randomize_mines(mines, start_from, rng);
-            let mut solver = S::new(&result);
-            if solver.solve(start_from) {
-                break;
+            let clusters = if S::is_hopeless(&result, start_from) {
+                Vec::new()
+            } else {
+                let mut solver = S::new(&result);
+                if solver.solve(start_from) {
+                    break;
+                }
+                solver.get_unsolvable_clusters()
+            };
+            if clusters.is_empty() || shuffles == MAX_SHUFFLE {
+                result = Self::new_unchecked(height, width, mines, Some(start_from));
+                result.randomize_mines(mines, start_from, rng);
+                shuffles = 0;
+            } else {
+                result.shuffle(clusters, rng);
+                shuffles += 1;
+            }
+        }
+        if !R::STANDARD {
+            result.apply_number_rule(rule)?;
+            if !S::supports_number_rules() && !result.has_standard_numbers() {
+                return Err(Error::UnsupportedNumberRule);
             }
         }
         Ok(result)
     }
 
+    /// Creates a new instance from a visual layout, one string per row.
+    /// This is what the [`board!`](crate::board) macro expands to.
+    ///
+    /// Each character is a cell: `*` is a mine, `.` is a safe cell
+    /// and a digit is a safe cell surrounded by that many mines. Whitespaces are ignored.
+    /// No check is performed on the number of mines nor on the safe zone around `start_from`.
+    ///
+    /// # Panics
+    /// Panics if the layout is empty, if the rows have different lengths,
+    /// if a character is not recognized, if a digit doesn't match the mines around it
+    /// or if `start_from` is out of bounds.
+    pub fn from_layout(rows: &[&str], start_from: Coordinate) -> Self {
+        let rows: Vec<Vec<char>> = rows
+            .iter()
+            .map(|row| row.chars().filter(|c| !c.is_whitespace()).collect())
+            .collect();
+        let (height, width) = (rows.len(), rows.first().map_or(0, Vec::len));
+        assert!(height > 0 && width > 0, "the layout is empty");
+        assert!(
+            rows.iter().all(|row| row.len() == width),
+            "all the rows of the layout must have the same length"
+        );
+        assert!(
+            start_from.0 < height && start_from.1 < width,
+            "the starting point {:?} is out of the layout",
+            start_from
+        );
+        let mines = rows.iter().flatten().filter(|&&c| c == '*').count();
+        let mut result = Self::new_unchecked(height, width, mines, Some(start_from));
+        for (index, &c) in rows.iter().flatten().enumerate() {
+            match c {
+                '*' => {
+                    result.cells_mut()[index].content = CellContent::Mine;
+                    result.increment_neighbors(index);
+                }
+                '.' | '0'..='8' => {}
+                c => panic!("unknown character {:?} in the layout", c),
+            }
+        }
+        for (index, &c) in rows.iter().flatten().enumerate() {
+            if let Some(number) = c.to_digit(10) {
+                assert_eq!(
+                    result.cells()[index].content,
+                    CellContent::Number(number as u8),
+                    "wrong number at {:?}",
+                    (index / width, index % width)
+                );
+            }
+        }
+        result
+    }
+
     /// Creates a new instance.
-    fn new_unchecked(height: usize, width: usize, mines: usize, start_from: Coordinate) -> Self {
+    fn new_unchecked(
+        height: usize,
+        width: usize,
+        mines: usize,
+        start_from: Option<Coordinate>,
+    ) -> Self {
+        let cells = MatrixCells::with_size(height, width).unwrap();
         Self {
-            height,
-            width,
-            mines,
-            cells: vec![vec![Cell::default(); width]; height],
-            start_from,
-            opened: 0,
-            flagged: 0,
-            exploded: 0,
+            grid: MSGrid::from_storage(cells, mines, start_from),
+            placer: Self::place_mines_dyn::<NonDeterministic>,
+            standard_numbers: true,
             seed: 0,
         }
     }
 
+    fn cells(&self) -> &[Cell] {
+        &self.grid.storage().cells
+    }
+
+    /// Returns the cells to change their content, before any of them is opened.
+    fn cells_mut(&mut self) -> &mut [Cell] {
+        &mut self.grid.storage_mut().cells
+    }
+
+    /// Returns the index of the given coordinate in the flat grid.
+    fn index(&self, (r, c): Coordinate) -> usize {
+        r * self.grid.width() + c
+    }
+
     /// Randomizes the positions of mines when initializing the board.
-    fn randomize_mines(&mut self, mines: usize, start_from: Coordinate, rng: &mut impl Rng) {
+    fn randomize_mines(&mut self, mines: usize, start_from: Coordinate, rng: &mut impl Random) {
+        let (height, width) = (self.grid.height(), self.grid.width());
         let mut mines_left = mines;
-        let mut must_be_safe = iter_neighbors(start_from, self.height, self.width)
-            .unwrap()
-            .collect::<Vec<_>>();
-        must_be_safe.push(start_from);
         while mines_left > 0 {
-            let coord @ (r, c) = (rng.gen_range(0..self.height), rng.gen_range(0..self.width));
-            if let CellContent::Number(_) = self.cells[r][c].content {
-                if !must_be_safe.contains(&coord) {
-                    self.cells[r][c].content = CellContent::Mine;
-                    self.increment_neighbors(coord);
+            let coord = (rng.below(height), rng.below(width));
+            let index = self.index(coord);
+            if let CellContent::Number(_) = self.cells()[index].content {
+                if !is_in_safe_zone(coord, start_from) {
+                    self.cells_mut()[index].content = CellContent::Mine;
+                    self.increment_neighbors(index);
                     mines_left -= 1;
                 }
             }
@@ -98,77 +413,111 @@ impl MSMatrix {
     }
 
     /// Increments the value of all neighboring non-mine cells when initializing the board.
-    fn increment_neighbors(&mut self, coord: Coordinate) {
-        iter_neighbors(coord, self.height, self.width)
-            .unwrap()
-            .for_each(|(r, c)| {
-                if let CellContent::Number(n) = self.cells[r][c].content {
-                    self.cells[r][c].content = CellContent::Number(n + 1);
-                }
-            });
+    fn increment_neighbors(&mut self, index: usize) {
+        let MatrixCells { cells, neighbors } = self.grid.storage_mut();
+        for neighbor in neighbors.of(index) {
+            if let CellContent::Number(n) = cells[neighbor].content {
+                cells[neighbor].content = CellContent::Number(n + 1);
+            }
+        }
     }
 
-    /// Checks the validity of a coordinate.
-    fn check_coordinate(&self, (r, c): Coordinate) -> Result<()> {
-        if r < self.height && c < self.width {
-            Ok(())
-        } else {
-            Err(Error::OutOfBounds)
+    fn decrement_neighbors(&mut self, index: usize) {
+        let MatrixCells { cells, neighbors } = self.grid.storage_mut();
+        for neighbor in neighbors.of(index) {
+            if let CellContent::Number(n) = cells[neighbor].content {
+                cells[neighbor].content = CellContent::Number(n - 1);
+            }
         }
     }
 
-    fn decrement_neighbors(&mut self, coord: Coordinate) {
-        iter_neighbors(coord, self.height, self.width)
-            .unwrap()
-            .for_each(|(r, c)| {
-                if let CellContent::Number(n) = self.cells[r][c].content {
-                    self.cells[r][c].content = CellContent::Number(n - 1);
-                }
-            });
+    /// Counts the mines around the cell at the given index.
+    fn count_neighboring_mines(&self, index: usize) -> u8 {
+        let MatrixCells { cells, neighbors } = self.grid.storage();
+        neighbors
+            .of(index)
+            .filter(|&neighbor| cells[neighbor].content == CellContent::Mine)
+            .count() as u8
     }
 
-    fn extract_mine(&mut self, coord @ (r, c): Coordinate) {
-        self.decrement_neighbors(coord);
-        self.cells[r][c].content = CellContent::Number(count_neighboring_mines(self, coord));
+    fn extract_mine(&mut self, index: usize) {
+        self.decrement_neighbors(index);
+        self.cells_mut()[index].content = CellContent::Number(self.count_neighboring_mines(index));
     }
 
     #[allow(unused)]
-    fn swap_cells(&mut self, old_mine @ (r1, c1): Coordinate, new_mine @ (r2, c2): Coordinate) {
+    fn swap_cells(&mut self, old_mine: Coordinate, new_mine: Coordinate) {
+        let (old_mine, new_mine) = (self.index(old_mine), self.index(new_mine));
         if cfg!(test) {
             // println!("Swapping cells {:?} and {:?}", old_mine, new_mine);
-            assert_eq!(self.cells[r1][c1].content, CellContent::Mine);
-            assert_ne!(self.cells[r2][c2].content, CellContent::Mine);
+            assert_eq!(self.cells()[old_mine].content, CellContent::Mine);
+            assert_ne!(self.cells()[new_mine].content, CellContent::Mine);
         }
         self.extract_mine(old_mine);
-        self.cells[r2][c2].content = CellContent::Mine;
+        self.cells_mut()[new_mine].content = CellContent::Mine;
         self.increment_neighbors(new_mine);
     }
 
-    #[allow(unused)]
-    fn shuffle(&mut self, clusters: Vec<Vec<Coordinate>>, rng: &mut impl Rng) {
+    /// Moves one mine inside each of the given clusters to a random non-mine cell of the same cluster.
+    /// Clusters without mines or without non-mine cells are left untouched.
+    ///
+    /// Numbers are [recomputed](MSMatrix::recompute_numbers) once at the end
+    /// instead of being updated after every single move.
+    fn shuffle(&mut self, clusters: Vec<Vec<Coordinate>>, rng: &mut impl Random) {
+        let mut changed = Vec::with_capacity(clusters.len() * 2);
         for cluster in clusters {
-            let mut from_mine;
-            loop {
-                from_mine = *cluster.choose(rng).unwrap();
-                if self.cells[from_mine.0][from_mine.1].content == CellContent::Mine {
-                    break;
-                }
+            let (mines, safe): (Vec<_>, Vec<_>) = cluster
+                .iter()
+                .map(|&coord| self.index(coord))
+                .partition(|&index| self.cells()[index].content == CellContent::Mine);
+            if !mines.is_empty() && !safe.is_empty() {
+                let from_mine = mines[rng.below(mines.len())];
+                let to_cell = safe[rng.below(safe.len())];
+                self.cells_mut()[from_mine].content = CellContent::Number(0);
+                self.cells_mut()[to_cell].content = CellContent::Mine;
+                changed.push(from_mine);
+                changed.push(to_cell);
             }
-            let mut to_cell;
-            loop {
-                to_cell = *cluster.choose(rng).unwrap();
-                if self.cells[to_cell.0][to_cell.1].content != CellContent::Mine {
-                    break;
-                }
+        }
+        self.recompute_numbers(changed);
+    }
+
+    /// Recomputes the numbers of the given cells and of their neighbors, each one only once,
+    /// after mines have been moved without updating the neighbors.
+    fn recompute_numbers(&mut self, changed: impl IntoIterator<Item = usize>) {
+        let mut affected = HashSet::new();
+        for index in changed {
+            affected.insert(index);
+            affected.extend(self.grid.storage().neighbors.of(index));
+        }
+        for index in affected {
+            if let CellContent::Number(_) = self.cells()[index].content {
+                self.cells_mut()[index].content =
+                    CellContent::Number(self.count_neighboring_mines(index));
             }
-            self.swap_cells(from_mine, to_cell);
         }
     }
 
+    /// Returns an estimate of the memory used by this instance, in bytes.
+    ///
+    /// The estimate includes the struct itself, the allocated capacity of the grid
+    /// and the table of neighbors.
+    pub fn memory_footprint(&self) -> usize {
+        let MatrixCells { cells, neighbors } = self.grid.storage();
+        size_of::<Self>()
+            + cells.capacity() * size_of::<Cell>()
+            + neighbors.offsets.capacity() * size_of::<Vec<isize>>()
+            + neighbors
+                .offsets
+                .iter()
+                .map(|offsets| offsets.capacity() * size_of::<isize>())
+                .sum::<usize>()
+    }
+
     #[cfg(test)]
     #[allow(unused)]
     fn print_raw(&self) {
-        for row in &self.cells {
+        for row in self.cells().chunks(self.grid.width()) {
             for cell in row {
                 print!("{} ", cell.content);
             }
@@ -179,109 +528,124 @@ impl MSMatrix {
 }
 
 impl MineSweeper for MSMatrix {
-    fn from_rng(
-        difficulty: Difficulty,
-        start_from: Coordinate,
-        rng: &mut impl Rng,
+    fn from_setup(setup: ValidSetup, rng: &mut impl Random) -> Result<Self> {
+        Self::from_setup::<NonDeterministic>(setup, rng)
+    }
+
+    fn from_setup_with_rule(
+        setup: ValidSetup,
+        rule: &mut impl NumberRule,
+        rng: &mut impl Random,
     ) -> Result<Self> {
-        Self::from_rng::<NonDeterministic>(difficulty, start_from, rng)
+        Self::from_setup_with_rule::<NonDeterministic, _>(setup, rule, rng)
     }
 
-    /// Implements all the additional rules suggested in the [trait interface](MineSweeper::open).
-    ///
-    /// The opening procedure is made using a [queue](VecDeque) (not recursive).
-    fn open(&mut self, coord @ (r, c): Coordinate) -> Result<OpenResult> {
-        self.check_coordinate(coord)?;
-        let (mut cells_opened, mut mines_exploded) = (0, 0);
-        let mut queue = VecDeque::from([coord]);
-        while !queue.is_empty() {
-            let coord @ (r, c) = queue.pop_front().unwrap();
-            if self.cells[r][c].state != CellState::Flagged {
-                if self.cells[r][c].state == CellState::Closed {
-                    self.cells[r][c].state = CellState::Open;
-                    cells_opened += 1;
-                    if self.cells[r][c].content == CellContent::Mine {
-                        mines_exploded += 1;
-                    }
-                }
-                if let CellContent::Number(neighboring_mines) = self.cells[r][c].content {
-                    if neighboring_mines == 0
-                        || count_neighboring_flags(self, coord) >= neighboring_mines
-                    {
-                        queue.extend(
-                            iter_neighbors((r, c), self.height, self.width)
-                                .unwrap()
-                                .filter(|&(r, c)| self.cells[r][c].state != CellState::Open),
-                        );
-                    }
-                }
-            }
+    fn new_unstarted(difficulty: Difficulty) -> Result<Self> {
+        Self::new_unstarted::<NonDeterministic>(difficulty)
+    }
+
+    /// Places the mines with the solver given to [`new_unstarted`](MSMatrix::new_unstarted).
+    fn place_mines_from_rng(
+        &mut self,
+        start_from: Coordinate,
+        rng: &mut impl Random,
+    ) -> Result<()> {
+        (self.placer)(self, start_from, rng)
+    }
+
+    /// Only recomputes the numbers if the rule is not [standard](NumberRule::STANDARD)
+    /// or the numbers were computed by another rule.
+    fn apply_number_rule<R: NumberRule>(&mut self, rule: &mut R) -> Result<()> {
+        shared::check_number_rule(self)?;
+        if !(R::STANDARD && self.standard_numbers) {
+            self.standard_numbers = true;
+            shared::apply_number_rule(self, rule, |ms, coord, number| {
+                let index = ms.index(coord);
+                ms.cells_mut()[index].content = CellContent::Number(number);
+                ms.standard_numbers &= number == ms.count_neighboring_mines(index);
+            })?;
         }
-        self.opened += cells_opened;
-        self.exploded += mines_exploded;
-        Ok(OpenResult::new(
-            self.cells[r][c],
-            cells_opened,
-            mines_exploded,
-        ))
-    }
-
-    fn open_one(&mut self, coord @ (r, c): Coordinate) -> Result<CellContent> {
-        self.check_coordinate(coord)?;
-        if self.cells[r][c].state == CellState::Closed {
-            self.cells[r][c].state = CellState::Open;
-            self.opened += 1;
-            if self.cells[r][c].content == CellContent::Mine {
-                self.exploded += 1;
-            }
+        self.grid.set_opens_neighbors(R::OPENS_NEIGHBORS);
+        Ok(())
+    }
+
+    fn opens_neighbors(&self, number: u8, flags: u8) -> bool {
+        self.grid.opens_neighbors(number, flags)
+    }
+
+    /// Whether the numbers are standard is updated each time a rule is applied.
+    fn has_standard_numbers(&self) -> bool {
+        self.standard_numbers
+    }
+
+    /// Places the mines with the solver given to [`new_unstarted`](MSMatrix::new_unstarted),
+    /// then [opens](MSGrid::open) the cells as [`MSGrid`] does.
+    fn open(&mut self, coord: Coordinate) -> Result<OpenResult> {
+        if self.grid.started_from().is_none() {
+            <Self as MineSweeper>::place_mines(self, coord)?;
         }
-        Ok(self.cells[r][c].content)
+        self.grid.open(coord)
     }
 
-    fn toggle_flag(&mut self, coord @ (r, c): Coordinate) -> Result<CellState> {
-        self.check_coordinate(coord)?;
-        match self.cells[r][c].state {
-            CellState::Closed => {
-                self.cells[r][c].state = CellState::Flagged;
-                self.flagged += 1;
-                Ok(CellState::Flagged)
-            }
-            CellState::Flagged => {
-                self.cells[r][c].state = CellState::Closed;
-                self.flagged -= 1;
-                Ok(CellState::Closed)
-            }
-            _ => Err(Error::AlreadyOpen),
+    fn open_one(&mut self, coord: Coordinate) -> Result<CellContent> {
+        if self.grid.started_from().is_none() {
+            <Self as MineSweeper>::place_mines(self, coord)?;
         }
+        self.grid.open_one(coord)
     }
 
-    fn get_cell(&self, coord @ (r, c): Coordinate) -> Result<Cell> {
-        self.check_coordinate(coord)?;
-        Ok(self.cells[r][c])
+    fn toggle_flag(&mut self, coord: Coordinate) -> Result<CellState> {
+        self.grid.toggle_flag(coord)
+    }
+
+    fn get_cell(&self, coord: Coordinate) -> Result<Cell> {
+        self.grid.get_cell(coord)
+    }
+
+    /// Returns a copy of the underlying vector of cells.
+    fn cells_snapshot(&self) -> Vec<Cell> {
+        self.grid.cells_snapshot()
     }
 
     fn height(&self) -> usize {
-        self.height
+        self.grid.height()
     }
 
     fn width(&self) -> usize {
-        self.width
+        self.grid.width()
     }
 
     fn mines(&self) -> usize {
-        self.mines
+        self.grid.mines()
+    }
+
+    fn started_from(&self) -> Option<Coordinate> {
+        self.grid.started_from()
     }
 
-    fn started_from(&self) -> Coordinate {
-        self.start_from
+    fn position_hash(&self) -> u64 {
+        self.grid.position_hash()
     }
 
     fn get_game_state(&self) -> GameState {
-        GameState {
-            opened: self.opened,
-            flagged: self.flagged,
-            mines_left: self.mines - self.flagged - self.exploded,
-        }
+        self.grid.get_game_state()
+    }
+}
+
+/// Boards are compared by their cells and counters, regardless of the solver given to the constructor.
+impl PartialEq for MSMatrix {
+    fn eq(&self, other: &Self) -> bool {
+        self.grid == other.grid && self.seed == other.seed
+    }
+}
+
+impl Eq for MSMatrix {}
+
+/// Hashes the same fields compared by [`PartialEq`].
+impl Hash for MSMatrix {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.grid.hash(state);
+        self.seed.hash(state);
     }
 }
 
@@ -294,12 +658,11 @@ impl Display for MSMatrix {
 #[cfg(test)]
 impl From<(usize, usize, &[usize], (usize, usize))> for MSMatrix {
     fn from((height, width, mines, start_from): (usize, usize, &[usize], (usize, usize))) -> Self {
-        let mut result = Self::new_unchecked(height, width, mines.len(), (0, 0));
-        for coord @ (r, c) in mines.iter().map(|&i| (i / width, i % width)) {
-            result.cells[r][c].content = CellContent::Mine;
-            result.increment_neighbors(coord);
+        let mut result = Self::new_unchecked(height, width, mines.len(), Some(start_from));
+        for &index in mines {
+            result.cells_mut()[index].content = CellContent::Mine;
+            result.increment_neighbors(index);
         }
-        result.start_from = start_from;
         result
     }
 }
@@ -307,9 +670,18 @@ impl From<(usize, usize, &[usize], (usize, usize))> for MSMatrix {
 #[cfg(test)]
 #[allow(unused_imports)]
 mod tests {
-    use rand::{rngs::StdRng, thread_rng, SeedableRng};
-
-    use crate::{solver::CSPSolver, Coordinate, Difficulty, MSMatrix};
+    use std::{
+        collections::HashSet,
+        hash::{Hash, Hasher},
+    };
+
+    use super::NeighborTable;
+    use crate::{
+        board, default_random, neighbors,
+        solver::{CSPSolver, NonDeterministic, SPSolver, Solver},
+        validate_setup, CellContent, Coordinate, Difficulty, Error, LiarNumbers, MSMatrix,
+        MineSweeper, NumberRule, Pcg32, StandardNumbers,
+    };
 
     type MSFrom<'a> = (usize, usize, &'a [usize], (usize, usize));
 
@@ -335,6 +707,64 @@ mod tests {
         ],
     )];
 
+    #[test]
+    fn layout() {
+        let ms = board![
+            start: (3, 0);
+            "1 * 1 ."
+            "1 1 1 ."
+            ". . 1 1"
+            ". . 1 *"
+        ];
+        assert_eq!(ms, MSMatrix::from((4, 4, &[1, 15][..], (3, 0))));
+        assert_eq!(
+            board!["*..", "...", "..*",],
+            MSMatrix::from((3, 3, &[0, 8][..], (0, 0)))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "wrong number at (0, 1)")]
+    fn layout_wrong_number() {
+        board!["*2", ".."];
+    }
+
+    #[test]
+    fn hash() {
+        let boards: HashSet<_> = (0..20)
+            .map(|seed| {
+                let mut rng = Pcg32::new(seed % 5);
+                MSMatrix::from_rng::<NonDeterministic>(Difficulty::easy(), (0, 0), &mut rng)
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(boards.len(), 5);
+
+        let mut ms = boards.iter().next().unwrap().clone();
+        assert!(boards.contains(&ms));
+        ms.open((0, 0)).unwrap();
+        assert!(!boards.contains(&ms));
+    }
+
+    #[test]
+    fn neighbor_table() {
+        for (h, w) in [(1, 1), (1, 5), (5, 1), (2, 2), (9, 9), (16, 30)] {
+            let table = NeighborTable::new(h, w);
+            for r in 0..h {
+                for c in 0..w {
+                    let mut actual: Vec<_> = table
+                        .of(r * w + c)
+                        .map(|index| (index / w, index % w))
+                        .collect();
+                    let mut expected: Vec<_> = neighbors((r, c), h, w).collect();
+                    actual.sort();
+                    expected.sort();
+                    assert_eq!(actual, expected);
+                }
+            }
+        }
+    }
+
     #[test]
     fn swap_mines() {
         for (starting_point, swaps) in SWAP_DATA {
@@ -346,17 +776,183 @@ mod tests {
         }
     }
 
+    #[test]
+    fn shuffle() {
+        let mut rng = Pcg32::new(0);
+        for (starting_point, _) in SWAP_DATA {
+            let mut ms: MSMatrix = (*starting_point).into();
+            let (h, w, ..) = *starting_point;
+            let clusters = vec![
+                (0..h / 2)
+                    .flat_map(|r| (0..w).map(move |c| (r, c)))
+                    .collect(),
+                (h / 2..h)
+                    .flat_map(|r| (0..w).map(move |c| (r, c)))
+                    .collect(),
+            ];
+            ms.shuffle(clusters, &mut rng);
+            let mines: Vec<_> = (0..h * w)
+                .filter(|&i| ms.cells()[i].content == CellContent::Mine)
+                .collect();
+            assert_eq!(mines.len(), starting_point.2.len());
+            assert_eq!(ms, (h, w, mines.as_slice(), starting_point.3).into());
+        }
+    }
+
+    #[test]
+    fn generation_is_solvable() {
+        for seed in 0..20 {
+            let mut rng = Pcg32::new(seed);
+            let ms = MSMatrix::from_rng::<CSPSolver>(Difficulty::hard(), (0, 0), &mut rng).unwrap();
+            let mut solver = <CSPSolver as Solver<MSMatrix>>::new(&ms);
+            assert!(Solver::<MSMatrix>::solve(
+                &mut solver,
+                ms.started_from().unwrap()
+            ));
+        }
+    }
+
+    #[test]
+    fn deferred_generation_is_solvable() {
+        for seed in 0..5 {
+            let mut rng = Pcg32::new(seed);
+            let mut ms = MSMatrix::new_unstarted::<CSPSolver>(Difficulty::hard()).unwrap();
+            MineSweeper::place_mines_from_rng(&mut ms, (8, 15), &mut rng).unwrap();
+            let mut solver = <CSPSolver as Solver<MSMatrix>>::new(&ms);
+            assert!(Solver::<MSMatrix>::solve(&mut solver, (8, 15)));
+        }
+    }
+
     #[test]
     #[allow(unused)]
     fn smart_generation() {
         let n = 1000;
         for i in 0..n {
-            // let mut rng = StdRng::seed_from_u64(i);
-            let mut rng = thread_rng();
+            // let mut rng = Pcg32::new(i);
+            let mut rng = default_random();
             // let difficulty = Difficulty::custom(100, 100, 2000);
             let difficulty = Difficulty::medium();
             // let difficulty = Difficulty::hard();
             let ms = MSMatrix::from_rng::<CSPSolver>(difficulty, (0, 0), &mut rng);
         }
     }
+
+    #[test]
+    fn number_rules() {
+        let mut unstarted =
+            MSMatrix::new_unstarted::<NonDeterministic>(Difficulty::easy()).unwrap();
+        assert_eq!(
+            unstarted.apply_number_rule(&mut StandardNumbers),
+            Err(Error::NotStarted)
+        );
+
+        let standard = <MSMatrix as MineSweeper>::from_seed(Difficulty::easy(), (4, 4), 3).unwrap();
+        let mut ms = standard.clone();
+        ms.apply_number_rule(&mut StandardNumbers).unwrap();
+        assert_eq!(ms, standard);
+
+        // the same numbers that don't open the neighbors make a different board
+        struct Closed;
+        impl NumberRule for Closed {
+            fn number(
+                &mut self,
+                coord: Coordinate,
+                height: usize,
+                width: usize,
+                is_mine: &dyn Fn(Coordinate) -> bool,
+            ) -> u8 {
+                StandardNumbers.number(coord, height, width, is_mine)
+            }
+
+            const OPENS_NEIGHBORS: bool = false;
+        }
+        let mut closed = standard.clone();
+        closed.apply_number_rule(&mut Closed).unwrap();
+        assert_eq!(closed.cells_snapshot(), standard.cells_snapshot());
+        assert_ne!(closed, standard);
+        let hash = |ms: &MSMatrix| {
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            ms.hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_ne!(hash(&closed), hash(&standard));
+
+        ms.apply_number_rule(&mut LiarNumbers::new(3)).unwrap();
+        assert!(!ms.has_standard_numbers());
+        // the standard rule restores the numbers changed by another one
+        let mut restored = ms.clone();
+        restored.apply_number_rule(&mut StandardNumbers).unwrap();
+        assert!(restored.has_standard_numbers());
+        assert_eq!(restored, standard);
+        for (liar, cell) in ms
+            .cells_snapshot()
+            .into_iter()
+            .zip(standard.cells_snapshot())
+        {
+            match (liar.content, cell.content) {
+                (CellContent::Number(liar), CellContent::Number(n)) => {
+                    assert_eq!(liar.abs_diff(n), 1)
+                }
+                (liar, content) => assert_eq!(liar, content),
+            }
+        }
+        // the numbers can't be trusted, so each move opens a single cell
+        assert_eq!(ms.open((4, 4)).unwrap().cells_opened, 1);
+        assert_eq!(
+            ms.apply_number_rule(&mut StandardNumbers),
+            Err(Error::CellsAlreadyOpen)
+        );
+    }
+
+    #[test]
+    fn generate_with_number_rules() {
+        let setup = validate_setup(Difficulty::easy(), (4, 4)).unwrap();
+        let mut rng = Pcg32::new(0);
+        let liar = MSMatrix::from_setup_with_rule::<NonDeterministic, _>(
+            setup,
+            &mut LiarNumbers::new(0),
+            &mut rng,
+        )
+        .unwrap();
+        // the solvers refuse the boards whose numbers lie
+        assert!(<CSPSolver as Solver<MSMatrix>>::is_hopeless(&liar, (4, 4)));
+        let mut csp = <CSPSolver as Solver<MSMatrix>>::new(&liar);
+        assert!(!Solver::<MSMatrix>::solve(&mut csp, (4, 4)));
+        let mut sp = <SPSolver<MSMatrix> as Solver<MSMatrix>>::new(&liar);
+        assert!(!sp.solve((4, 4)));
+        assert_eq!(crate::solver::safe_cells(&liar), vec![]);
+        // so they can't be used to generate them
+        assert_eq!(
+            MSMatrix::from_setup_with_rule::<CSPSolver, _>(
+                setup,
+                &mut LiarNumbers::new(0),
+                &mut rng
+            ),
+            Err(Error::UnsupportedNumberRule)
+        );
+
+        let standard =
+            MSMatrix::from_setup_with_rule::<CSPSolver, _>(setup, &mut StandardNumbers, &mut rng)
+                .unwrap();
+        let mut csp = <CSPSolver as Solver<MSMatrix>>::new(&standard);
+        assert!(Solver::<MSMatrix>::solve(&mut csp, (4, 4)));
+
+        // the rule is refused before running the solver
+        struct Unused;
+        impl Solver<MSMatrix> for Unused {
+            fn new(_: &MSMatrix) -> Self {
+                unreachable!("the solver must not be created")
+            }
+            fn is_hopeless(_: &MSMatrix, _: Coordinate) -> bool {
+                unreachable!("the solver must not be called")
+            }
+            fn solve(&mut self, _: Coordinate) -> bool {
+                unreachable!()
+            }
+        }
+        assert_eq!(
+            MSMatrix::from_setup_with_rule::<Unused, _>(setup, &mut LiarNumbers::new(0), &mut rng),
+            Err(Error::UnsupportedNumberRule)
+        );
+    }
 }
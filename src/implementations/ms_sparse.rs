@@ -0,0 +1,251 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{Display, Formatter},
+    mem::size_of,
+};
+
+use crate::{
+    is_in_safe_zone, neighbors, shared, validate_difficulty, zobrist_key, Cell, CellContent,
+    CellState, Coordinate, Difficulty, Error, GameState, MineSweeper, NumberRule, OpenResult,
+    Random, Result, StandardNumbers, ValidSetup,
+};
+
+/// Represents a grid storing only mines, flags and opened cells.
+/// Use this for astronomically large boards (10⁷–10⁸ cells and more), where even a dense grid
+/// with one byte per cell would take too much memory.
+///
+/// Memory usage only depends on the number of mines and on how many cells have been opened or flagged,
+/// never on the size of the board.
+/// The number of a cell is computed on demand from the set of mines and
+/// cached once the cell is opened, so open cells are never counted twice.
+///
+/// # Solver
+/// The mines are placed at random, since boards this large are out of reach of the [solvers](crate::solver::Solver).
+///
+/// # Number rules
+/// Only the [standard](crate::NumberRule::STANDARD) numbers are supported,
+/// as any other rule would need the numbers of all the cells to be stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MSSparse {
+    height: usize,
+    width: usize,
+    mines: HashSet<Coordinate>,
+    flagged: HashSet<Coordinate>,
+    /// Opened cells with their cached content.
+    open: HashMap<Coordinate, CellContent>,
+    /// The number of mines, also before they are placed.
+    total_mines: usize,
+    start_from: Option<Coordinate>,
+    exploded: usize,
+    /// [`NumberRule::OPENS_NEIGHBORS`] of the rule that computed the numbers.
+    opens_neighbors: bool,
+    /// The [position hash](MineSweeper::position_hash), updated with each move.
+    position_hash: u64,
+}
+
+impl MSSparse {
+    /// Creates a new instance.
+    fn new_unchecked(height: usize, width: usize, mines: usize) -> Self {
+        Self {
+            height,
+            width,
+            mines: HashSet::with_capacity(mines),
+            flagged: Default::default(),
+            open: Default::default(),
+            total_mines: mines,
+            start_from: None,
+            exploded: 0,
+            opens_neighbors: StandardNumbers::OPENS_NEIGHBORS,
+            position_hash: 0,
+        }
+    }
+
+    /// Randomizes the positions of mines when initializing the board.
+    fn randomize_mines(&mut self, mines: usize, start_from: Coordinate, rng: &mut impl Random) {
+        while self.mines.len() < mines {
+            let coord = (rng.below(self.height), rng.below(self.width));
+            if !is_in_safe_zone(coord, start_from) {
+                self.mines.insert(coord);
+            }
+        }
+    }
+
+    /// Checks the validity of a coordinate.
+    fn check_coordinate(&self, coord @ (r, c): Coordinate) -> Result<()> {
+        if r < self.height && c < self.width {
+            Ok(())
+        } else {
+            Err(Error::OutOfBounds {
+                coord,
+                height: self.height,
+                width: self.width,
+            })
+        }
+    }
+
+    /// Changes the state of a cell, as required by the functions in [`shared`],
+    /// and updates the position hash.
+    /// The content of opened cells is cached.
+    fn set_state(&mut self, coord: Coordinate, state: CellState) {
+        let before = self.get_cell(coord).unwrap();
+        match state {
+            CellState::Open => {
+                let content = self.content(coord);
+                self.open.insert(coord, content);
+                if content == CellContent::Mine {
+                    self.exploded += 1;
+                }
+            }
+            CellState::Flagged => {
+                self.flagged.insert(coord);
+            }
+            CellState::Closed => {
+                self.flagged.remove(&coord);
+            }
+        }
+        self.position_hash ^=
+            zobrist_key(coord, before) ^ zobrist_key(coord, self.get_cell(coord).unwrap());
+    }
+
+    /// Returns the content of a cell, using the cached value if the cell is open.
+    fn content(&self, coord: Coordinate) -> CellContent {
+        if let Some(&content) = self.open.get(&coord) {
+            content
+        } else if self.mines.contains(&coord) {
+            CellContent::Mine
+        } else {
+            CellContent::Number(self.count_neighboring_mines(coord))
+        }
+    }
+
+    fn count_neighboring_mines(&self, coord: Coordinate) -> u8 {
+        neighbors(coord, self.height, self.width)
+            .filter(|coord| self.mines.contains(coord))
+            .count() as u8
+    }
+
+    /// Returns an estimate of the memory used by this instance, in bytes.
+    ///
+    /// The estimate includes the struct itself and the allocated capacity of the sets and maps,
+    /// counting one extra control byte per slot as done by the standard [`HashSet`](HashSet).
+    pub fn memory_footprint(&self) -> usize {
+        let slot = size_of::<Coordinate>() + 1;
+        size_of::<Self>()
+            + (self.mines.capacity() + self.flagged.capacity()) * slot
+            + self.open.capacity() * (slot + size_of::<CellContent>())
+    }
+}
+
+impl MineSweeper for MSSparse {
+    /// Creates a new game without checking the setup again.
+    fn from_setup(setup: ValidSetup, rng: &mut impl Random) -> Result<Self> {
+        let (height, width, mines) = setup.difficulty().into();
+        let mut result = Self::new_unchecked(height, width, mines);
+        result.randomize_mines(mines, setup.start_from(), rng);
+        result.start_from = Some(setup.start_from());
+        Ok(result)
+    }
+
+    fn new_unstarted(difficulty: Difficulty) -> Result<Self> {
+        validate_difficulty(difficulty)?;
+        let (height, width, mines) = difficulty.into();
+        Ok(Self::new_unchecked(height, width, mines))
+    }
+
+    fn place_mines_from_rng(
+        &mut self,
+        start_from: Coordinate,
+        rng: &mut impl Random,
+    ) -> Result<()> {
+        if self.start_from.is_some() {
+            return Err(Error::AlreadyStarted);
+        }
+        self.check_coordinate(start_from)?;
+        self.randomize_mines(self.total_mines, start_from, rng);
+        self.start_from = Some(start_from);
+        Ok(())
+    }
+
+    /// Only accepts the [standard](NumberRule::STANDARD) rules, whose numbers are already counted on demand.
+    /// Any other rule returns [`UnsupportedNumberRule`](Error::UnsupportedNumberRule),
+    /// since storing its numbers would take memory proportional to the size of the board.
+    fn apply_number_rule<R: NumberRule>(&mut self, _rule: &mut R) -> Result<()> {
+        if !R::STANDARD {
+            return Err(Error::UnsupportedNumberRule);
+        }
+        shared::check_number_rule(self)?;
+        self.opens_neighbors = R::OPENS_NEIGHBORS;
+        Ok(())
+    }
+
+    fn opens_neighbors(&self, number: u8, flags: u8) -> bool {
+        self.opens_neighbors && flags >= number
+    }
+
+    /// The numbers are always counted from the mines.
+    fn has_standard_numbers(&self) -> bool {
+        true
+    }
+
+    /// Implements all the additional rules suggested in the [trait interface](MineSweeper::open).
+    ///
+    /// The opening procedure is delegated to [`shared::open`].
+    fn open(&mut self, coord: Coordinate) -> Result<OpenResult> {
+        shared::open(self, coord, Self::set_state)
+    }
+
+    fn open_one(&mut self, coord: Coordinate) -> Result<CellContent> {
+        shared::open_one(self, coord, Self::set_state)
+    }
+
+    fn toggle_flag(&mut self, coord: Coordinate) -> Result<CellState> {
+        shared::toggle_flag(self, coord, Self::set_state)
+    }
+
+    fn get_cell(&self, coord: Coordinate) -> Result<Cell> {
+        self.check_coordinate(coord)?;
+        let state = if self.open.contains_key(&coord) {
+            CellState::Open
+        } else if self.flagged.contains(&coord) {
+            CellState::Flagged
+        } else {
+            CellState::Closed
+        };
+        Ok(Cell::new(state, self.content(coord)))
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn mines(&self) -> usize {
+        self.total_mines
+    }
+
+    fn started_from(&self) -> Option<Coordinate> {
+        self.start_from
+    }
+
+    fn position_hash(&self) -> u64 {
+        self.position_hash
+    }
+
+    fn get_game_state(&self) -> GameState {
+        GameState::new(
+            self.total_mines,
+            self.open.len(),
+            self.flagged.len(),
+            self.exploded,
+        )
+    }
+}
+
+impl Display for MSSparse {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        MineSweeper::fmt(self, f)
+    }
+}
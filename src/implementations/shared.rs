@@ -0,0 +1,102 @@
+//! The standard game rules, written in terms of a few primitives.
+//!
+//! A custom implementation only needs to store the board: [`get_cell`](MineSweeper::get_cell)
+//! reads a cell and a `set_state` function changes the state of a cell.
+//! The functions in this module can then be called from the methods of [`MineSweeper`]
+//! to get the same rules as the provided implementations.
+//!
+//! `set_state` is only called when the state of a cell actually changes,
+//! so it is the right place to update counters. The possible changes are:
+//! - from [`Closed`](CellState::Closed) to [`Open`](CellState::Open)
+//! - from [`Closed`](CellState::Closed) to [`Flagged`](CellState::Flagged)
+//! - from [`Flagged`](CellState::Flagged) to [`Closed`](CellState::Closed)
+
+use crate::{
+    flood_open, CellContent, CellState, Coordinate, Error, MineSweeper, NumberRule, OpenResult,
+    Result,
+};
+
+/// Implements [`MineSweeper::open`] using [`flood_open`].
+/// If the mines have not been placed yet, they are placed starting from the given cell.
+pub fn open<M: MineSweeper>(
+    ms: &mut M,
+    coord: Coordinate,
+    mut set_state: impl FnMut(&mut M, Coordinate, CellState),
+) -> Result<OpenResult> {
+    if ms.started_from().is_none() {
+        ms.place_mines(coord)?;
+    }
+    flood_open(ms, coord, |ms, coord| set_state(ms, coord, CellState::Open))
+}
+
+/// Implements [`MineSweeper::open_one`]: opens the cell if it is closed and returns its content.
+/// Flagged cells are not opened.
+/// If the mines have not been placed yet, they are placed starting from the given cell.
+pub fn open_one<M: MineSweeper>(
+    ms: &mut M,
+    coord: Coordinate,
+    mut set_state: impl FnMut(&mut M, Coordinate, CellState),
+) -> Result<CellContent> {
+    if ms.started_from().is_none() {
+        ms.place_mines(coord)?;
+    }
+    let cell = ms.get_cell(coord)?;
+    if cell.state == CellState::Closed {
+        set_state(ms, coord, CellState::Open);
+    }
+    Ok(cell.content)
+}
+
+/// Implements [`MineSweeper::toggle_flag`]: toggles the flag on a closed cell and returns the new state.
+/// Returns [`AlreadyOpen`](Error::AlreadyOpen) if the cell is open.
+pub fn toggle_flag<M: MineSweeper>(
+    ms: &mut M,
+    coord: Coordinate,
+    mut set_state: impl FnMut(&mut M, Coordinate, CellState),
+) -> Result<CellState> {
+    let state = match ms.get_cell(coord)?.state {
+        CellState::Open => return Err(Error::AlreadyOpen),
+        CellState::Closed => CellState::Flagged,
+        CellState::Flagged => CellState::Closed,
+    };
+    set_state(ms, coord, state);
+    Ok(state)
+}
+
+/// Checks that a [number rule](NumberRule) can be applied to the board.
+/// Returns [`NotStarted`](Error::NotStarted) if the mines have not been placed yet
+/// and [`CellsAlreadyOpen`](Error::CellsAlreadyOpen) if some cells are already open.
+pub fn check_number_rule(ms: &impl MineSweeper) -> Result<()> {
+    if ms.started_from().is_none() {
+        return Err(Error::NotStarted);
+    }
+    if ms.get_game_state().opened > 0 {
+        return Err(Error::CellsAlreadyOpen);
+    }
+    Ok(())
+}
+
+/// Implements [`MineSweeper::apply_number_rule`]: computes the number of each safe cell with the rule
+/// and calls `set_number` with it, after the checks of [`check_number_rule`].
+///
+/// The implementor must then follow [`NumberRule::OPENS_NEIGHBORS`] in
+/// [`opens_neighbors`](MineSweeper::opens_neighbors).
+pub fn apply_number_rule<M: MineSweeper, R: NumberRule>(
+    ms: &mut M,
+    rule: &mut R,
+    mut set_number: impl FnMut(&mut M, Coordinate, u8),
+) -> Result<()> {
+    check_number_rule(ms)?;
+    let (height, width) = (ms.height(), ms.width());
+    let cells = ms.cells_snapshot();
+    let is_mine = |(r, c): Coordinate| cells[r * width + c].content == CellContent::Mine;
+    for r in 0..height {
+        for c in 0..width {
+            if !is_mine((r, c)) {
+                let number = rule.number((r, c), height, width, &is_mine);
+                set_number(ms, (r, c), number);
+            }
+        }
+    }
+    Ok(())
+}
@@ -1,14 +1,17 @@
 use std::{
     any::type_name,
     fmt::{Debug, Display},
+    mem::size_of,
 };
 
-use rand::{rngs::StdRng, thread_rng, Rng, SeedableRng};
 use test_data::{MSFrom, TestAction, OPEN_DATA};
 
 use crate::{
-    iter_neighbors, CellContent, Difficulty, Error, GameState, MSHash, MSMatrix, MineSweeper,
-    Result,
+    compute_position_hash, default_random, neighbors,
+    solver::{CSPSolver, NonDeterministic, Solver},
+    validate_setup, Cell, CellContent, CellState, Difficulty, Error, GameState, LiarNumbers,
+    MSConst, MSHash, MSMatrix, MSSparse, MineSweeper, MineSweeperExt, Pcg32, Random, Result,
+    StandardNumbers,
 };
 
 mod test_data;
@@ -18,12 +21,12 @@ mod test_data;
 // #[allow(unused_assignments)]
 fn play() {
     fn test<M: MineSweeper + Display>(#[allow(unused)] seed: u64) {
-        // let mut rng = StdRng::seed_from_u64(seed);
-        let mut rng = thread_rng();
+        // let mut rng = Pcg32::new(seed);
+        let mut rng = default_random();
 
         let difficulty = Difficulty::easy();
         let (h, w, m) = difficulty.into();
-        let start_from = (rng.gen_range(0..h), rng.gen_range(0..w));
+        let start_from = (rng.below(h), rng.below(w));
         let mut ms = M::from_rng(difficulty, start_from, &mut rng).unwrap();
 
         assert_eq!(ms.height(), h);
@@ -34,7 +37,7 @@ fn play() {
         for i in 0..h {
             for j in 0..w {
                 if let CellContent::Mine = ms.get_cell((i, j)).unwrap().content {
-                    if rng.gen_range(0..100) <= 60 {
+                    if rng.below(100) <= 60 {
                         assert!(ms.toggle_flag((i, j)).is_ok());
                     }
                 }
@@ -54,19 +57,21 @@ fn play() {
     for seed in 0..1 {
         test::<MSMatrix>(seed);
         test::<MSHash>(seed);
+        test::<MSSparse>(seed);
+        test::<MSConst<9, 9>>(seed);
     }
 }
 
 #[test]
 fn invalid_number_of_mines() {
     fn test<M: MineSweeper>(#[allow(unused)] seed: u64) {
-        // let mut rng = StdRng::seed_from_u64(seed);
-        let mut rng = thread_rng();
+        // let mut rng = Pcg32::new(seed);
+        let mut rng = default_random();
 
-        let (h, w) = (rng.gen_range(4..100), rng.gen_range(4..100));
+        let (h, w) = (4 + rng.below(100 - 4), 4 + rng.below(100 - 4));
         let mut m = w * h;
         let mut difficulty = Difficulty::custom(h, w, m);
-        let start_from = (rng.gen_range(0..h), rng.gen_range(0..w));
+        let start_from = (rng.below(h), rng.below(w));
 
         check_success(M::from_rng(difficulty, start_from, &mut rng));
 
@@ -81,7 +86,7 @@ fn invalid_number_of_mines() {
 
     fn check_success<M: MineSweeper>(ms: Result<M>) {
         match ms {
-            Err(Error::TooManyMines) => (),
+            Err(Error::TooManyMines { mines, max }) => assert!(mines > max),
             Err(_) => {
                 panic!(
                     "Wrong error: {}::new should panic with Error::TooManyMines!",
@@ -95,28 +100,25 @@ fn invalid_number_of_mines() {
     for seed in 0..10 {
         test::<MSMatrix>(seed);
         test::<MSHash>(seed);
+        test::<MSSparse>(seed);
     }
 }
 
 #[test]
 fn start_from() {
     fn test<M: MineSweeper>(#[allow(unused)] seed: u64) {
-        // let mut rng = StdRng::seed_from_u64(seed);
-        let mut rng = thread_rng();
+        // let mut rng = Pcg32::new(seed);
+        let mut rng = default_random();
 
         let difficulty = Difficulty::hard();
         let (h, w, _) = difficulty.into();
-        let start_from = (rng.gen_range(0..h), rng.gen_range(0..w));
+        let start_from = (rng.below(h), rng.below(w));
         let mut ms: M = M::new(difficulty, start_from).unwrap();
 
-        assert!(
-            ms.open(start_from).unwrap().cells_opened
-                >= iter_neighbors(start_from, h, w).unwrap().count()
-        );
+        assert!(ms.open(start_from).unwrap().cells_opened >= neighbors(start_from, h, w).count());
 
-        let mut should_be_safe = iter_neighbors(start_from, h, w)
-            .unwrap()
-            .map(|(r, c)| ms.get_cell((r, c)).unwrap().content);
+        let mut should_be_safe =
+            neighbors(start_from, h, w).map(|(r, c)| ms.get_cell((r, c)).unwrap().content);
 
         assert_eq!(
             ms.get_cell(start_from).unwrap().content,
@@ -128,21 +130,22 @@ fn start_from() {
     for seed in 0..10 {
         test::<MSMatrix>(seed);
         test::<MSHash>(seed);
+        test::<MSSparse>(seed);
     }
 }
 
 #[test]
 fn invalid_start_from() {
     fn test<M: MineSweeper>(#[allow(unused)] seed: u64) {
-        // let mut rng = StdRng::seed_from_u64(seed);
-        let mut rng = thread_rng();
+        // let mut rng = Pcg32::new(seed);
+        let mut rng = default_random();
 
         let difficulty = Difficulty::hard();
         let (h, w, _) = difficulty.into();
         let start_from = (h, w);
 
         match M::from_rng(difficulty, start_from, &mut rng) {
-            Err(Error::OutOfBounds) => (),
+            Err(Error::OutOfBounds { coord, .. }) => assert_eq!(coord, start_from),
             Err(_) => {
                 panic!(
                     "Wrong error: {}::new should panic with Error::OutOfBounds!",
@@ -159,6 +162,7 @@ fn invalid_start_from() {
     for seed in 0..10 {
         test::<MSMatrix>(seed);
         test::<MSHash>(seed);
+        test::<MSSparse>(seed);
     }
 }
 
@@ -171,12 +175,12 @@ fn compare_implementations() {
         M1: MineSweeper + Display,
         M2: MineSweeper + Display,
     {
-        let mut rng = StdRng::seed_from_u64(seed);
-        // let mut rng = thread_rng();
+        let mut rng = Pcg32::new(seed);
+        // let mut rng = default_random();
 
         let difficulty = Difficulty::hard();
         let (h, w, _) = difficulty.into();
-        let start_from = (rng.gen_range(0..h), rng.gen_range(0..w));
+        let start_from = (rng.below(h), rng.below(w));
         let mut ms_1 = M1::from_rng(difficulty, start_from, &mut rng.clone()).unwrap();
         let mut ms_2 = M2::from_rng(difficulty, start_from, &mut rng.clone()).unwrap();
 
@@ -188,7 +192,7 @@ fn compare_implementations() {
             for j in 0..w {
                 assert_eq!(ms_1.get_cell((i, j)), ms_2.get_cell((i, j)));
                 if let CellContent::Mine = ms_1.get_cell((i, j)).unwrap().content {
-                    if rng.gen_range(0..100) <= 5 {
+                    if rng.below(100) <= 5 {
                         assert_eq!(ms_1.toggle_flag((i, j)), ms_2.toggle_flag((i, j)));
                     }
                 }
@@ -211,6 +215,221 @@ fn compare_implementations() {
 
     for seed in 0..1 {
         test::<MSMatrix, MSHash>(seed);
+        test::<MSMatrix, MSSparse>(seed);
+        test::<MSMatrix, MSConst<16, 30>>(seed);
+        #[cfg(feature = "heapless")]
+        test::<MSMatrix, crate::MSHeapless<480>>(seed);
+    }
+}
+
+#[test]
+fn toggle_flag() {
+    fn test<M: MineSweeper>() {
+        let mut ms = M::from_rng(Difficulty::easy(), (0, 0), &mut Pcg32::new(0)).unwrap();
+        let coord = (8, 8);
+        assert_eq!(ms.toggle_flag(coord), Ok(CellState::Flagged));
+        assert_eq!(ms.get_cell(coord).unwrap().state, CellState::Flagged);
+        ms.open_one(coord).unwrap();
+        assert_eq!(ms.get_cell(coord).unwrap().state, CellState::Flagged);
+        assert_eq!(ms.toggle_flag(coord), Ok(CellState::Closed));
+        assert_eq!(ms.get_cell(coord).unwrap().state, CellState::Closed);
+        assert_eq!(ms.get_game_state().flagged, 0);
+
+        ms.open_one((0, 0)).unwrap();
+        assert_eq!(ms.toggle_flag((0, 0)), Err(Error::AlreadyOpen));
+        assert!(matches!(
+            ms.toggle_flag((9, 0)),
+            Err(Error::OutOfBounds { .. })
+        ));
+    }
+
+    test::<MSMatrix>();
+    test::<MSHash>();
+    test::<MSSparse>();
+    test::<MSConst<9, 9>>();
+}
+
+#[test]
+fn unstarted() {
+    fn test<M: MineSweeper>(seed: u64) {
+        let mut rng = Pcg32::new(seed);
+        let difficulty = Difficulty::easy();
+        let (h, w, m) = difficulty.into();
+        let mut ms = M::new_unstarted(difficulty).unwrap();
+        assert_eq!(ms.started_from(), None);
+        assert_eq!(ms.mines(), m);
+        assert_eq!(ms.toggle_flag((h - 1, w - 1)), Ok(CellState::Flagged));
+        assert_eq!(
+            ms.place_mines_from_rng((h, 0), &mut rng),
+            Err(Error::OutOfBounds {
+                coord: (h, 0),
+                height: h,
+                width: w
+            })
+        );
+
+        let start_from = (rng.below(h), rng.below(w));
+        ms.place_mines_from_rng(start_from, &mut rng).unwrap();
+        assert_eq!(ms.started_from(), Some(start_from));
+        assert_eq!(
+            ms.place_mines_from_rng(start_from, &mut rng),
+            Err(Error::AlreadyStarted)
+        );
+        assert_eq!(
+            ms.get_cell((h - 1, w - 1)).unwrap().state,
+            CellState::Flagged
+        );
+        assert_eq!(ms.get_game_state().flagged, 1);
+        let mines = (0..h)
+            .flat_map(|r| (0..w).map(move |c| (r, c)))
+            .filter(|&coord| ms.get_cell(coord).unwrap().content == CellContent::Mine)
+            .count();
+        assert_eq!(mines, m);
+        assert_eq!(
+            ms.open(start_from).unwrap().cell.content,
+            CellContent::Number(0)
+        );
+
+        // The first open places the mines.
+        let start_from = (rng.below(h), rng.below(w));
+        let mut ms = M::new_unstarted(difficulty).unwrap();
+        assert!(ms.open((h, w)).is_err());
+        assert_eq!(ms.started_from(), None);
+        let result = ms.open(start_from).unwrap();
+        assert_eq!(ms.started_from(), Some(start_from));
+        assert_eq!(result.cell.content, CellContent::Number(0));
+        assert_eq!(result.mines_exploded, 0);
+        let mut ms = M::new_unstarted(difficulty).unwrap();
+        assert_eq!(ms.open_one(start_from), Ok(CellContent::Number(0)));
+        assert_eq!(ms.started_from(), Some(start_from));
+
+        assert!(matches!(
+            M::new_unstarted((3, 3, 1).into()),
+            Err(Error::TooManyMines { mines: 1, max: 0 })
+        ));
+    }
+
+    for seed in 0..10 {
+        test::<MSMatrix>(seed);
+        test::<MSHash>(seed);
+        test::<MSSparse>(seed);
+        test::<MSConst<9, 9>>(seed);
+    }
+}
+
+#[test]
+fn cells_snapshot() {
+    fn test<M: MineSweeper>(seed: u64) {
+        let mut rng = Pcg32::new(seed);
+        let difficulty = Difficulty::medium();
+        let (h, w, _) = difficulty.into();
+        let mut ms = M::from_rng(difficulty, (0, 0), &mut rng).unwrap();
+        ms.open((0, 0)).unwrap();
+        ms.toggle_flag((h - 1, w - 1)).unwrap();
+        let snapshot = ms.cells_snapshot();
+        assert_eq!(snapshot.len(), h * w);
+        for r in 0..h {
+            for c in 0..w {
+                assert_eq!(snapshot[r * w + c], ms.get_cell((r, c)).unwrap());
+            }
+        }
+    }
+
+    for seed in 0..5 {
+        test::<MSMatrix>(seed);
+        test::<MSHash>(seed);
+        test::<MSSparse>(seed);
+        test::<MSConst<16, 16>>(seed);
+    }
+}
+
+#[test]
+fn number_rules() {
+    fn test<M: MineSweeper + Debug>(seed: u64) {
+        let setup = validate_setup(Difficulty::medium(), (0, 0)).unwrap();
+        let standard = M::from_setup(setup, &mut Pcg32::new(seed)).unwrap();
+        let mut liar =
+            M::from_setup_with_rule(setup, &mut LiarNumbers::new(seed), &mut Pcg32::new(seed))
+                .unwrap();
+        assert!(standard.has_standard_numbers());
+        assert!(!liar.has_standard_numbers());
+        for (liar, cell) in liar
+            .cells_snapshot()
+            .into_iter()
+            .zip(standard.cells_snapshot())
+        {
+            match (liar.content, cell.content) {
+                (CellContent::Number(liar), CellContent::Number(n)) => {
+                    assert_eq!(liar.abs_diff(n), 1)
+                }
+                (liar, content) => assert_eq!(liar, content),
+            }
+        }
+        // the shared open path follows the rule, so each move opens a single cell
+        assert_eq!(liar.open((0, 0)).unwrap().cells_opened, 1);
+        assert_eq!(
+            liar.apply_number_rule(&mut StandardNumbers),
+            Err(Error::CellsAlreadyOpen)
+        );
+
+        let mut unstarted = M::new_unstarted(Difficulty::medium()).unwrap();
+        assert_eq!(
+            unstarted.apply_number_rule(&mut StandardNumbers),
+            Err(Error::NotStarted)
+        );
+    }
+
+    for seed in 0..5 {
+        test::<MSMatrix>(seed);
+        test::<MSHash>(seed);
+        test::<MSConst<16, 16>>(seed);
+        #[cfg(feature = "heapless")]
+        test::<crate::MSHeapless<256>>(seed);
+    }
+
+    // storing the numbers of another rule would take memory proportional to the board
+    let setup = validate_setup(Difficulty::medium(), (0, 0)).unwrap();
+    assert_eq!(
+        MSSparse::from_setup_with_rule(setup, &mut LiarNumbers::new(0), &mut Pcg32::new(0)),
+        Err(Error::UnsupportedNumberRule)
+    );
+}
+
+#[test]
+fn position_hash() {
+    fn test<M: MineSweeper>(seed: u64) -> Vec<u64> {
+        let mut rng = Pcg32::new(seed);
+        let difficulty = Difficulty::medium();
+        let (h, w, _) = difficulty.into();
+        let mut ms = M::from_rng(difficulty, (0, 0), &mut rng).unwrap();
+        let mut hashes = vec![ms.position_hash()];
+        assert_eq!(ms.position_hash(), 0);
+        for _ in 0..50 {
+            let coord = (rng.below(h), rng.below(w));
+            if rng.below(10) < 3 {
+                let before = ms.position_hash();
+                if ms.toggle_flag(coord).is_ok() {
+                    assert_ne!(ms.position_hash(), before);
+                    ms.toggle_flag(coord).unwrap();
+                    assert_eq!(ms.position_hash(), before);
+                    ms.toggle_flag(coord).unwrap();
+                }
+            } else {
+                ms.open(coord).unwrap();
+            }
+            assert_eq!(ms.position_hash(), compute_position_hash(&ms));
+            hashes.push(ms.position_hash());
+        }
+        hashes
+    }
+
+    for seed in 0..5 {
+        let hashes = test::<MSMatrix>(seed);
+        assert_eq!(hashes, test::<MSHash>(seed));
+        assert_eq!(hashes, test::<MSSparse>(seed));
+        assert_eq!(hashes, test::<MSConst<16, 16>>(seed));
+        #[cfg(feature = "heapless")]
+        assert_eq!(hashes, test::<crate::MSHeapless<256>>(seed));
     }
 }
 
@@ -220,12 +439,12 @@ fn game_state() {
     where
         M: MineSweeper + Display + Debug,
     {
-        // let mut rng = StdRng::seed_from_u64(seed);
-        let mut rng = thread_rng();
+        // let mut rng = Pcg32::new(seed);
+        let mut rng = default_random();
 
         let difficulty = Difficulty::easy();
         let (h, w, m) = difficulty.into();
-        let start_from = (rng.gen_range(0..h), rng.gen_range(0..w));
+        let start_from = (rng.below(h), rng.below(w));
         let mut ms = M::from_rng(difficulty, start_from, &mut rng).unwrap();
 
         assert_eq!(ms.height(), h);
@@ -233,18 +452,19 @@ fn game_state() {
         assert_eq!(ms.mines(), m);
 
         // flags ~60% of the mines
-        let (mut flagged, mut mines_left, mut opened) = (0, m, 0);
+        let (mut flagged, mut mines_left, mut opened, mut exploded) = (0, m, 0, 0);
         for i in 0..h {
             for j in 0..w {
                 if let CellContent::Mine = ms.get_cell((i, j)).unwrap().content {
-                    if rng.gen_range(0..100) <= 60 {
+                    if rng.below(100) <= 60 {
                         assert!(ms.toggle_flag((i, j)).is_ok());
                         mines_left -= 1;
                         flagged += 1;
-                    } else if rng.gen_range(0..100) <= 50 {
+                    } else if rng.below(100) <= 50 {
                         assert_eq!(ms.open((i, j)).unwrap().mines_exploded, 1);
                         mines_left -= 1;
                         opened += 1;
+                        exploded += 1;
                     }
                 }
             }
@@ -262,29 +482,28 @@ fn game_state() {
 
                 opened += open_result.unwrap().cells_opened;
                 mines_left -= open_result.unwrap().mines_exploded;
+                exploded += open_result.unwrap().mines_exploded;
                 assert_eq!(
                     ms.get_game_state(),
-                    GameState {
-                        flagged,
-                        opened,
-                        mines_left
-                    }
+                    GameState::new(m, opened, flagged, exploded)
                 );
+                assert_eq!(ms.get_game_state().mines_left, mines_left);
             }
         }
         assert_eq!(
             ms.get_game_state(),
-            GameState {
-                flagged,
-                opened: h * w - flagged,
-                mines_left: 0
-            }
+            GameState::new(m, h * w - flagged, flagged, m - flagged)
         );
+        assert_eq!(ms.get_game_state().mines_left, 0);
     }
 
     for seed in 0..10 {
         test::<MSMatrix>(seed);
         test::<MSHash>(seed);
+        test::<MSSparse>(seed);
+        test::<MSConst<9, 9>>(seed);
+        #[cfg(feature = "heapless")]
+        test::<crate::MSHeapless<81>>(seed);
     }
 }
 
@@ -325,9 +544,52 @@ fn open_result() {
             ms.get_game_state().opened,
             ms.height() * ms.width() - ms.mines()
         );
+        assert!(ms.is_cleared());
     }
 
     for (tuple, results) in OPEN_DATA {
         test::<MSMatrix>(*tuple, results);
     }
 }
+
+#[cfg(feature = "heapless")]
+#[test]
+fn heapless_capacity() {
+    use crate::MSHeapless;
+
+    assert!(MSHeapless::<81>::new_unstarted(Difficulty::easy()).is_ok());
+    assert!(matches!(
+        MSHeapless::<80>::new_unstarted(Difficulty::easy()),
+        Err(Error::SizeMismatch {
+            height: 9,
+            width: 9,
+            capacity: 80
+        })
+    ));
+}
+
+#[test]
+fn memory_footprint() {
+    let mut rng = Pcg32::new(0);
+    let difficulty = Difficulty::custom(1000, 1000, 10);
+    let ms_matrix = MSMatrix::from_rng::<NonDeterministic>(difficulty, (0, 0), &mut rng).unwrap();
+    let ms_hash = <MSHash as MineSweeper>::from_rng(difficulty, (0, 0), &mut rng).unwrap();
+
+    assert!(ms_matrix.memory_footprint() >= 1000 * 1000 * size_of::<Cell>());
+    assert!(ms_hash.memory_footprint() < ms_matrix.memory_footprint());
+
+    let difficulty = Difficulty::custom(10_000, 10_000, 100);
+    let ms_sparse = <MSSparse as MineSweeper>::from_rng(difficulty, (0, 0), &mut rng).unwrap();
+    assert!(ms_sparse.memory_footprint() < 10_000);
+    // applied and checked without visiting the 10⁸ cells
+    let mut ms_sparse = ms_sparse;
+    ms_sparse.apply_number_rule(&mut StandardNumbers).unwrap();
+    assert!(ms_sparse.has_standard_numbers());
+    assert!(ms_sparse.memory_footprint() < 10_000);
+
+    let ms: MSMatrix = OPEN_DATA[0].0.into();
+    let mut solver = <CSPSolver as Solver<MSMatrix>>::new(&ms);
+    let before = solver.memory_footprint();
+    Solver::<MSMatrix>::solve(&mut solver, ms.started_from().unwrap());
+    assert!(solver.memory_footprint() >= before);
+}
@@ -5,9 +5,12 @@
 //! Import [`MineSweeper`](MineSweeper) and one of its implementations
 //! - [`MSMatrix`](MSMatrix) (recommended)
 //! - [`MSHash`](MSHash)
+//! - [`MSSparse`](MSSparse) (for very large boards)
+//! - [`MSConst`](MSConst) (for boards with a fixed size)
 //!
 //! to use it.
-//! ```
+#![cfg_attr(feature = "std", doc = "```")]
+#![cfg_attr(not(feature = "std"), doc = "```ignore")]
 //! use mine_sweeperr::{solver::NonDeterministic, Difficulty, MSMatrix, MineSweeper};
 //!
 //! // Create a new game with a 16x16 board and 40 mines
@@ -26,46 +29,98 @@
 //!
 //! A [working implementation](https://princic-1837592.github.io/mine_sweeper/index.html)
 //! of this library is available on [my GitHub page](https://Princic-1837592.github.io)
+//!
+//! Without the default `std` feature the crate is `no_std`, for embedded targets with an allocator:
+//! only the [`MineSweeper`] trait, [`MSGrid`], [`MSConst`], `MSHeapless` and the types they need are available.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 
-use std::fmt::{Display, Formatter};
+use alloc::{string::ToString, vec::Vec};
+use core::fmt::{Display, Formatter};
 
+#[cfg(feature = "tokio")]
+pub use async_game::*;
 pub use cell::*;
+pub use coordinate::*;
 pub use difficulty::*;
+pub use error::*;
+pub use ext::*;
+#[cfg(feature = "std")]
+pub use game::*;
 pub use implementations::*;
-use rand::Rng;
+pub use random::*;
+pub use rules::*;
+#[cfg(feature = "std")]
+pub use save::*;
+pub use setup::*;
+#[cfg(feature = "std")]
 use solver::Solver;
+#[cfg(feature = "std")]
+pub use storage::*;
 pub use utils::*;
+#[cfg(feature = "std")]
+pub use validate::*;
+pub use view::*;
 
+#[cfg(feature = "std")]
+pub mod analysis;
+#[cfg(feature = "egui")]
+pub mod egui;
+#[cfg(feature = "std")]
+pub mod env;
+pub mod frontend;
 mod implementations;
+#[cfg(feature = "std")]
 mod macros;
+#[cfg(feature = "std")]
+pub mod puzzle;
+#[cfg(feature = "std")]
+pub mod simulate;
+#[cfg(feature = "std")]
 pub mod solver;
+#[cfg(feature = "ndarray")]
+pub mod tensor;
+#[cfg(feature = "tui")]
+pub mod tui;
 mod utils;
+#[cfg(target_family = "wasm")]
+pub mod wasm;
 
+#[cfg(feature = "tokio")]
+mod async_game;
 mod cell;
+mod coordinate;
 mod difficulty;
-#[cfg(test)]
+mod error;
+mod ext;
+#[cfg(feature = "std")]
+mod game;
+mod random;
+mod rules;
+#[cfg(feature = "std")]
+mod save;
+mod setup;
+#[cfg(feature = "std")]
+mod storage;
+#[cfg(all(test, feature = "std"))]
 mod tests;
+#[cfg(feature = "std")]
+mod validate;
+mod view;
 
-/// A pair of zero-based coordinates. The first coordinate is the row, the second is the column.
-pub type Coordinate = (usize, usize);
 /// The result of some potentially wrong action.
-type Result<T> = std::result::Result<T, Error>;
-
-/// Error type for the [`MineSweeper`](MineSweeper) game.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum Error {
-    OutOfBounds,
-    AlreadyOpen,
-    TooManyMines,
-    InvalidParameters,
-}
+///
+/// Like [`std::io::Result`](std::io::Result), this is a shorthand for
+/// [`core::result::Result`](core::result::Result) with the [`Error`](Error) of this crate.
+pub type Result<T> = core::result::Result<T, Error>;
 
 /// The result of opening a [`cell`](Cell).
 ///
 /// Contains information about the content of the opened cell,
 /// how many cells have been opened in total,
 /// how many mines have been found (exploded) during the process.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct OpenResult {
     pub cell: Cell,
     pub cells_opened: usize,
@@ -83,15 +138,35 @@ impl OpenResult {
 }
 
 /// Represents the current state of the game
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// New fields may be added in the future,
+/// so implementations outside of this crate create it with [`GameState::new`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub struct GameState {
     pub flagged: usize,
     pub opened: usize,
-    /// This is simply the number of mines minus the number of flagged cells.
+    /// The number of open mines, which are also counted in `opened`.
+    pub exploded: usize,
+    /// This is simply the number of mines minus the number of flagged cells and exploded mines,
+    /// or zero if they are more than the mines.
     /// This takes into consideration flags regardless of whether they are correct or not.
     pub mines_left: usize,
 }
 
+impl GameState {
+    /// Creates the state of a game on a board with the given number of mines,
+    /// computing [`mines_left`](GameState::mines_left) from the other counters.
+    pub const fn new(mines: usize, opened: usize, flagged: usize, exploded: usize) -> Self {
+        GameState {
+            flagged,
+            opened,
+            exploded,
+            mines_left: mines.saturating_sub(flagged).saturating_sub(exploded),
+        }
+    }
+}
+
 /// Represents a board with its cells.
 ///
 /// Declares methods to create a new instance, to open and flag cells
@@ -107,33 +182,155 @@ pub trait MineSweeper: Sized {
     ///
     /// # Suggested Errors
     /// - [`TooManyMines`](Error::TooManyMines) if the number of mines is greater than the number of cells.
-    /// The "number of cells" is intended as the total number of cells minus the 9 safe cells granted as starting point.
+    ///   The "number of cells" is intended as the total number of cells minus the 9 safe cells granted as starting point.
     /// - [`InvalidParameters`](Error::InvalidParameters) if the number of rows or columns is `0`.
     /// - [`OutOfBounds`](Error::OutOfBounds) if the starting point is out of bounds.
     ///
-    /// If not overridden, the default rng used is [`rand::thread_rng()`](rand::thread_rng()).
+    /// These are the checks performed by [`validate_setup`],
+    /// before the game is created with [`from_setup`](MineSweeper::from_setup).
+    ///
+    /// If not overridden, the default rng used is `rand::thread_rng()`,
+    /// or a [`Pcg32`] with a random seed if the `rand` feature is disabled.
     fn new(difficulty: Difficulty, start_from: Coordinate) -> Result<Self> {
-        Self::from_rng(difficulty, start_from, &mut rand::thread_rng())
+        Self::from_rng(difficulty, start_from, &mut default_random())
+    }
+    /// Creates a new instance of the game using a [`Pcg32`] generator with the given seed,
+    /// to reproduce a specific game on every platform.
+    fn from_seed(difficulty: Difficulty, start_from: Coordinate, seed: u64) -> Result<Self> {
+        Self::from_rng(difficulty, start_from, &mut Pcg32::new(seed))
     }
     /// Creates a new instance of the game using the given random generator.
     /// Can be used to test the game or to reproduce a specific game by passing a seeded rng.
-    fn from_rng(difficulty: Difficulty, start_from: Coordinate, rng: &mut impl Rng)
-        -> Result<Self>;
+    fn from_rng(
+        difficulty: Difficulty,
+        start_from: Coordinate,
+        rng: &mut impl Random,
+    ) -> Result<Self> {
+        Self::from_setup(validate_setup(difficulty, start_from)?, rng)
+    }
+    /// Creates a new instance of the game from a setup that was already checked by [`validate_setup`],
+    /// using the given random generator.
+    /// All the other constructors end up here.
+    ///
+    /// # Suggested Errors
+    /// Only the ones specific to the implementation, since the setup doesn't need to be checked again.
+    fn from_setup(setup: ValidSetup, rng: &mut impl Random) -> Result<Self>;
+    /// Creates a new instance of the game like [`from_setup`](MineSweeper::from_setup),
+    /// with the numbers computed by the given [`NumberRule`].
+    ///
+    /// If not overridden, the rule is applied with [`apply_number_rule`](MineSweeper::apply_number_rule)
+    /// right after the mines are placed.
+    fn from_setup_with_rule(
+        setup: ValidSetup,
+        rule: &mut impl NumberRule,
+        rng: &mut impl Random,
+    ) -> Result<Self> {
+        let mut result = Self::from_setup(setup, rng)?;
+        result.apply_number_rule(rule)?;
+        Ok(result)
+    }
+    /// Creates a new instance of the game whose mines are not placed yet,
+    /// for when the starting point is not known in advance.
+    /// The mines are placed by the first call to [`open`](MineSweeper::open),
+    /// or explicitly with [`place_mines`](MineSweeper::place_mines).
+    /// Until then, [`started_from`](MineSweeper::started_from) returns `None`.
+    ///
+    /// # Suggested Errors
+    /// The same as [`new`](MineSweeper::new), except for those about the starting point.
+    /// These are the checks performed by [`validate_difficulty`].
+    fn new_unstarted(difficulty: Difficulty) -> Result<Self>;
+    /// Places the mines on a board created with [`new_unstarted`](MineSweeper::new_unstarted),
+    /// with the same guarantees on the starting point given by [`new`](MineSweeper::new).
+    /// Flags placed before are kept.
+    ///
+    /// # Suggested Errors
+    /// - [`AlreadyStarted`](Error::AlreadyStarted) if the mines have already been placed.
+    /// - [`OutOfBounds`](Error::OutOfBounds) if the starting point is out of bounds.
+    ///
+    /// If not overridden, the default rng is the same used by [`new`](MineSweeper::new).
+    fn place_mines(&mut self, start_from: Coordinate) -> Result<()> {
+        self.place_mines_from_rng(start_from, &mut default_random())
+    }
+    /// Places the mines using the given random generator, like [`place_mines`](MineSweeper::place_mines).
+    fn place_mines_from_rng(&mut self, start_from: Coordinate, rng: &mut impl Random)
+        -> Result<()>;
+    /// Replaces the numbers of the safe cells with the ones computed by the given rule,
+    /// which also decides [when opening a cell opens its neighbors](MineSweeper::opens_neighbors).
+    /// Apply the rule after the mines are placed and before opening any cell.
+    ///
+    /// # Suggested Errors
+    /// - [`NotStarted`](Error::NotStarted) if the mines have not been placed yet.
+    /// - [`CellsAlreadyOpen`](Error::CellsAlreadyOpen) if some cells are already open.
+    ///
+    /// These are the checks performed by [`shared::check_number_rule`].
+    ///
+    /// If not overridden, returns [`UnsupportedNumberRule`](Error::UnsupportedNumberRule)
+    /// for any rule, so that implementations without variants don't need to implement it.
+    ///
+    #[cfg_attr(feature = "std", doc = "```")]
+    #[cfg_attr(not(feature = "std"), doc = "```ignore")]
+    /// use mine_sweeperr::{board, CellContent, LiarNumbers, MineSweeper};
+    ///
+    /// let mut ms = board!["*..", "...", "..."];
+    /// ms.apply_number_rule(&mut LiarNumbers::new(0)).unwrap();
+    /// // the empty cell shows a 1 and doesn't open its neighbors
+    /// assert_eq!(ms.open((2, 2)).unwrap().cell.content, CellContent::Number(1));
+    /// assert_eq!(ms.get_game_state().opened, 1);
+    /// ```
+    fn apply_number_rule<R: NumberRule>(&mut self, _rule: &mut R) -> Result<()> {
+        Err(Error::UnsupportedNumberRule)
+    }
+    /// Returns `true` if opening a cell showing `number` with `flags` flagged neighbors
+    /// also opens its other neighbors, both when flooding empty areas and when chording.
+    ///
+    /// The default implementation follows the [standard numbers](StandardNumbers),
+    /// implementations should override it to follow the [`NumberRule`] given to
+    /// [`apply_number_rule`](MineSweeper::apply_number_rule).
+    fn opens_neighbors(&self, number: u8, flags: u8) -> bool {
+        flags >= number
+    }
+    /// Returns `true` if each number is the count of the mines around its cell,
+    /// as with [`StandardNumbers`].
+    /// The solvers only work on these boards, since the other [rules](NumberRule)
+    /// don't let them deduce anything from the numbers.
+    ///
+    /// The default implementation checks each number against a [snapshot](MineSweeper::cells_snapshot)
+    /// of the board, implementations should override it if they know which rule computed the numbers.
+    fn has_standard_numbers(&self) -> bool {
+        let (height, width) = (self.height(), self.width());
+        let cells = self.cells_snapshot();
+        let is_mine = |(r, c): Coordinate| cells[r * width + c].content == CellContent::Mine;
+        (0..height)
+            .flat_map(|r| (0..width).map(move |c| (r, c)))
+            .all(|coord| match cells[coord.0 * width + coord.1].content {
+                CellContent::Number(n) => {
+                    neighbors(coord, height, width)
+                        .filter(|&neighbor| is_mine(neighbor))
+                        .count()
+                        == n as usize
+                }
+                CellContent::Mine => true,
+            })
+    }
     /// Tries to open a cell.
     ///
     /// Returns an error if the cell is out of bounds,
     /// otherwise returns an [`OpenResult`](OpenResult).
     ///
+    /// If the mines have not been [placed](MineSweeper::place_mines) yet,
+    /// they are placed starting from the given cell, which is therefore safe.
+    ///
     /// # Implementation
     /// The opening procedure should respect the following rules,
     /// that are not enforced by the game but make the user experience better:
     /// - if the opened cell is a number and it's surrounded by enough flags,
-    /// all the neighboring non-flagged cells are considered safe to open
-    /// and should therefore be opened
+    ///   all the neighboring non-flagged cells are considered safe to open
+    ///   and should therefore be opened
     /// - the opening procedure should not stop at the first mine found,
-    /// but should keep opening until all safe neighboring cells are opened
+    ///   but should keep opening until all safe neighboring cells are opened
     fn open(&mut self, coord: Coordinate) -> Result<OpenResult>;
     /// Opens a single cell. May be useful in tests and when implementing a [`Solver`](solver::Solver).
+    /// Like [`open`](MineSweeper::open), it places the mines if they have not been placed yet.
     fn open_one(&mut self, coord: Coordinate) -> Result<CellContent>;
     /// Tries to toggle the flag on a cell and returns the new state.
     ///
@@ -143,26 +340,47 @@ pub trait MineSweeper: Sized {
     fn toggle_flag(&mut self, coord: Coordinate) -> Result<CellState>;
     /// Returns the state of the given cell.
     fn get_cell(&self, coord: Coordinate) -> Result<Cell>;
+    /// Returns all the cells of the board, row after row:
+    /// the cell at `(r, c)` is at index `r * width + c`.
+    ///
+    /// The default implementation calls [`get_cell`](MineSweeper::get_cell) for each cell,
+    /// implementations should override it if they can do better.
+    fn cells_snapshot(&self) -> Vec<Cell> {
+        let (height, width) = (self.height(), self.width());
+        (0..height)
+            .flat_map(|r| (0..width).map(move |c| (r, c)))
+            .map(|coord| self.get_cell(coord).unwrap())
+            .collect()
+    }
     /// Returns the height of the board.
     fn height(&self) -> usize;
     /// Returns the width of the board.
     fn width(&self) -> usize;
     /// Returns the number of mines of the board.
     fn mines(&self) -> usize;
-    /// Returns the first cell opened
-    fn started_from(&self) -> Coordinate;
+    /// Returns the first cell opened, or `None` if the mines have not been placed yet.
+    fn started_from(&self) -> Option<Coordinate>;
     /// Returns the current state of the game
     fn get_game_state(&self) -> GameState;
+    /// Returns a hash of the position as seen by the player: which cells are open or flagged
+    /// and the content of the open ones. Equal positions have equal hashes on all the implementations.
+    ///
+    /// The hash is the xor of the [keys](zobrist_key) of all the cells, so it can be updated with each move.
+    /// The default implementation computes it from scratch,
+    /// while the provided implementations keep it updated.
+    fn position_hash(&self) -> u64 {
+        compute_position_hash(self)
+    }
     /// Displays the grid in a human-readable format as a grid of characters or emojis representing cells.
     ///
     /// - If `#` is given as formatting option, it will be passed to the cells to [format them as emojis](Cell::fmt).
     /// - If the precision parameter `.0` is passed, row and columns numbers will be printed
-    /// on the top and left of the grid. No other number is allowed as precision at the moment.
+    ///   on the top and left of the grid. No other number is allowed as precision at the moment.
     /// - You can combine `#.0` to print both cells and row-column numbers as emojis.
     ///
     /// The default implementation relies on the implementation of [`get_cell`](MineSweeper::get_cell),
     /// [`height`](MineSweeper::height) and [`width`](MineSweeper::width).
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         let (use_emojis, print_numbers) = (f.alternate(), f.precision() == Some(0));
         let max_height_digits = (self.height() - 1).to_string().len();
         if print_numbers {
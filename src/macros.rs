@@ -1,14 +1,37 @@
+/// Builds an [`MSMatrix`](crate::MSMatrix) from a visual layout, one string per row.
+///
+/// `*` is a mine, `.` is a safe cell and a digit is a safe cell surrounded by that many mines
+/// (checked when building the board). Whitespaces are ignored.
+/// The game starts from `(0, 0)` unless a different starting point is given before the rows.
+/// See [`MSMatrix::from_layout`](crate::MSMatrix::from_layout) for the cases that panic.
+///
+/// # Examples
+/// ```
+/// use mine_sweeperr::{board, CellContent, MineSweeper};
+///
+/// let ms = board![
+///     "* 2 ."
+///     "2 * ."
+///     ". . ."
+/// ];
+/// assert_eq!(ms.mines(), 2);
+/// assert_eq!(ms.get_cell((0, 1)).unwrap().content, CellContent::Number(2));
+///
+/// let ms = board![start: (2, 2); "*..", "...", "..."];
+/// assert_eq!(ms.started_from(), Some((2, 2)));
+/// ```
 #[macro_export]
-macro_rules! check {
-    ($difficulty:ident, $start_from:ident) => {
-        if $difficulty.2 >= $difficulty.0 * $difficulty.1 - 9 {
-            return Err(Error::TooManyMines);
-        }
-        if $difficulty.0 == 0 || $difficulty.1 == 0 {
-            return Err(Error::InvalidParameters);
-        }
-        if $start_from.0 >= $difficulty.0 || $start_from.1 >= $difficulty.1 {
-            return Err(Error::OutOfBounds);
-        }
+macro_rules! board {
+    (start: $start_from:expr; $($row:literal),+ $(,)?) => {
+        $crate::MSMatrix::from_layout(&[$($row),+], $start_from)
+    };
+    (start: $start_from:expr; $($row:literal)+) => {
+        $crate::MSMatrix::from_layout(&[$($row),+], $start_from)
+    };
+    ($($row:literal),+ $(,)?) => {
+        $crate::MSMatrix::from_layout(&[$($row),+], (0, 0))
+    };
+    ($($row:literal)+) => {
+        $crate::MSMatrix::from_layout(&[$($row),+], (0, 0))
     };
 }
@@ -0,0 +1,90 @@
+//! "Find the safe cell" puzzles: positions where exactly one closed cell can be proven safe.
+//!
+//! ```
+//! use mine_sweeperr::{puzzle::Puzzle, Difficulty};
+//!
+//! let puzzle = Puzzle::generate(Difficulty::easy(), 1).unwrap();
+//! assert!(puzzle.is_solution(puzzle.safe));
+//! println!("{puzzle}");
+//! ```
+
+use std::fmt::{Display, Formatter};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    solver::safe_cells, BoardView, Cell, CellContent, CellState, CellView, Coordinate, Difficulty,
+    MSMatrix, MineSweeper, MineSweeperExt, Pcg32, Random,
+};
+
+/// The number of boards tried by [`Puzzle::generate`] before giving up.
+pub const MAX_ATTEMPTS: u64 = 100;
+
+/// A partially revealed board where exactly one closed cell is provably safe,
+/// according to [`safe_cells`].
+///
+/// With the `serde` feature this can be serialized, for example to JSON,
+/// without leaking the position of the mines.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Puzzle {
+    /// The position shown to the player. No cell is flagged.
+    pub view: BoardView,
+    /// The only closed cell that is provably safe.
+    pub safe: Coordinate,
+}
+
+impl Puzzle {
+    /// Generates a puzzle from a seed, always the same for the same seed and difficulty.
+    ///
+    /// Each attempt plays a board generated from the seed, opening random provably safe cells
+    /// until only one of them is left. Returns `None` if no puzzle is found
+    /// in [`MAX_ATTEMPTS`] boards, or if the difficulty is not [valid](crate::validate_difficulty).
+    pub fn generate(difficulty: Difficulty, seed: u64) -> Option<Self> {
+        let start_from = (difficulty.height() / 2, difficulty.width() / 2);
+        (0..MAX_ATTEMPTS).find_map(|attempt| {
+            let seed = seed.wrapping_add(attempt);
+            let mut ms = <MSMatrix as MineSweeper>::from_seed(difficulty, start_from, seed).ok()?;
+            let mut rng = Pcg32::new(!seed);
+            ms.open(start_from).ok()?;
+            while !ms.is_cleared() {
+                match safe_cells(&ms).as_slice() {
+                    [] => return None,
+                    &[safe] => {
+                        return Some(Puzzle {
+                            view: BoardView::new(&ms),
+                            safe,
+                        })
+                    }
+                    safe => ms.open(safe[rng.below(safe.len())]).ok()?,
+                };
+            }
+            None
+        })
+    }
+
+    /// Returns `true` if the given cell is the answer to the puzzle.
+    pub fn is_solution(&self, coord: Coordinate) -> bool {
+        coord == self.safe
+    }
+}
+
+impl Display for Puzzle {
+    /// Prints the position in the same format as [`MineSweeper::fmt`], without row and column numbers.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for row in self.view.cells.chunks(self.view.width) {
+            for &cell in row {
+                let cell = match cell {
+                    CellView::Closed => Cell::closed(),
+                    CellView::Flagged => Cell::new(CellState::Flagged, CellContent::Number(0)),
+                    CellView::Number(n) => Cell::new(CellState::Open, CellContent::Number(n)),
+                    CellView::Mine => Cell::new(CellState::Open, CellContent::Mine),
+                };
+                cell.fmt(f)?;
+            }
+            f.write_str("\n")?;
+        }
+        Ok(())
+    }
+}
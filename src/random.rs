@@ -0,0 +1,119 @@
+#[cfg(not(feature = "std"))]
+use core::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(all(feature = "std", not(feature = "rand")))]
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+};
+
+/// A source of random numbers, used to place the mines.
+///
+/// With the `rand` feature (enabled by default) this is implemented for every [`rand::RngCore`],
+/// so any generator of the `rand` crate can be passed to [`from_rng`](crate::MineSweeper::from_rng).
+/// Without it, the crate uses its own [`Pcg32`] generator.
+pub trait Random {
+    /// Returns the next random number.
+    fn next_u64(&mut self) -> u64;
+
+    /// Returns a number uniformly distributed in `0..n`. `n` must be greater than `0`.
+    fn below(&mut self, n: usize) -> usize {
+        let n = n as u64;
+        // Rejects the last incomplete group of `n` numbers, so that the result is not biased.
+        let zone = u64::MAX - (u64::MAX - n + 1) % n;
+        loop {
+            let x = self.next_u64();
+            if x <= zone {
+                return (x % n) as usize;
+            }
+        }
+    }
+}
+
+#[cfg(feature = "rand")]
+impl<R: rand::RngCore + ?Sized> Random for R {
+    fn next_u64(&mut self) -> u64 {
+        rand::RngCore::next_u64(self)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        rand::Rng::gen_range(self, 0..n)
+    }
+}
+
+/// A small [PCG](https://www.pcg-random.org) generator (`PCG-XSH-RR` with 64 bits of state).
+///
+/// It is used by [`from_seed`](crate::MineSweeper::from_seed), so that the same seed
+/// produces the same board on every platform and with every set of features,
+/// and by [`new`](crate::MineSweeper::new) when the `rand` feature is disabled.
+/// Without the `std` feature these boards are not random: pass a generator fed by a source of entropy
+/// of the target to [`from_rng`](crate::MineSweeper::from_rng) instead.
+/// It is not suitable for cryptographic purposes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Pcg32 {
+    state: u64,
+    increment: u64,
+}
+
+impl Pcg32 {
+    const MULTIPLIER: u64 = 6364136223846793005;
+
+    /// Creates a new generator from the given seed.
+    pub fn new(seed: u64) -> Self {
+        let mut result = Self {
+            state: 0,
+            increment: 1442695040888963407,
+        };
+        result.next_u32();
+        result.state = result.state.wrapping_add(seed);
+        result.next_u32();
+        result
+    }
+
+    /// Creates a new generator seeded from the random keys that the standard library
+    /// uses for its hash maps.
+    #[cfg(all(feature = "std", not(feature = "rand")))]
+    pub(crate) fn from_entropy() -> Self {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u64(0);
+        Self::new(hasher.finish())
+    }
+
+    /// Without the standard library there is no source of entropy,
+    /// so each generator is seeded with the next value of a counter.
+    /// The counter is only loaded and stored, which is supported by all the targets.
+    #[cfg(not(feature = "std"))]
+    pub(crate) fn from_entropy() -> Self {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let seed = COUNTER.load(Ordering::Relaxed);
+        COUNTER.store(seed.wrapping_add(1), Ordering::Relaxed);
+        Self::new(seed as u64)
+    }
+
+    /// Returns the next 32 random bits.
+    pub fn next_u32(&mut self) -> u32 {
+        let state = self.state;
+        self.state = state
+            .wrapping_mul(Self::MULTIPLIER)
+            .wrapping_add(self.increment);
+        let xor_shifted = (((state >> 18) ^ state) >> 27) as u32;
+        xor_shifted.rotate_right((state >> 59) as u32)
+    }
+}
+
+impl Random for Pcg32 {
+    fn next_u64(&mut self) -> u64 {
+        (self.next_u32() as u64) << 32 | self.next_u32() as u64
+    }
+}
+
+/// Returns the generator used when none is given.
+#[cfg(feature = "rand")]
+pub(crate) fn default_random() -> impl Random {
+    rand::thread_rng()
+}
+
+/// Returns the generator used when none is given.
+#[cfg(not(feature = "rand"))]
+pub(crate) fn default_random() -> impl Random {
+    Pcg32::from_entropy()
+}
@@ -0,0 +1,159 @@
+use crate::{neighbors, Coordinate, Pcg32, Random};
+
+/// The optional rules of a [`Game`](crate::Game).
+///
+/// The [default](GameRules::default) rules are the classic ones:
+/// opening an open number chords, flags are not limited, the game is lost at the first mine,
+/// the player chooses the first cell, there are no question marks and the last mines are not flagged automatically.
+///
+/// New rules may be added in future releases, so the struct can't be built field by field:
+/// start from the default rules and change the ones you need,
+/// or choose them with a [`GameBuilder`](crate::GameBuilder).
+///
+/// ```
+/// # use mine_sweeperr::GameRules;
+/// let mut rules = GameRules::default();
+/// rules.lives = 3;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct GameRules {
+    /// Opening an open number surrounded by enough flags opens its other neighbors.
+    /// If `false`, opening an open cell returns [`AlreadyOpen`](crate::Error::AlreadyOpen).
+    pub chord: bool,
+    /// There can't be more flags than mines.
+    /// If `true`, placing a flag when all the mines are flagged returns
+    /// [`TooManyFlags`](crate::Error::TooManyFlags).
+    pub flag_limit: bool,
+    /// The number of mines that can be opened before the game is lost.
+    /// Once it's lost, all the moves return [`GameOver`](crate::Error::GameOver).
+    /// A game has at least one life, so `0` is played as `1`.
+    pub lives: usize,
+    /// How the first cell of the game is opened.
+    pub start: StartPolicy,
+    /// Toggling the flag on a flagged cell marks it with a question mark,
+    /// toggling it again removes the mark.
+    /// The marks are kept by the [`Game`](crate::Game) and can be read with
+    /// [`is_question_marked`](crate::Game::is_question_marked).
+    pub question_marks: bool,
+    /// When all the safe cells are open, the remaining cells are flagged.
+    pub auto_finish: bool,
+}
+
+impl Default for GameRules {
+    fn default() -> Self {
+        GameRules {
+            chord: true,
+            flag_limit: false,
+            lives: 1,
+            start: StartPolicy::Free,
+            question_marks: false,
+            auto_finish: false,
+        }
+    }
+}
+
+/// How the first cell of a [`Game`](crate::Game) is opened, one of the [rules](GameRules) of the game.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum StartPolicy {
+    /// The player opens the first cell.
+    /// If the mines of the board are not placed yet, they are placed starting from that cell.
+    #[default]
+    Free,
+    /// The cell the board [started from](crate::MineSweeper::started_from) is opened
+    /// as the first move of the game, which is passed to the hooks registered with the
+    /// [`GameBuilder`](crate::GameBuilder), including [autosaving](crate::GameBuilder::autosave).
+    /// If the mines are not placed yet, they are placed starting from the center of the board,
+    /// with the [seed](crate::GameBuilder::seed) of the game if given.
+    /// Nothing is opened if some cells already are.
+    OpenStart,
+}
+
+/// Computes the numbers shown on the safe cells, to play variants of the game.
+///
+/// Generate a board with a rule using [`from_setup_with_rule`](crate::MineSweeper::from_setup_with_rule),
+/// or apply it to a board whose mines are placed with [`apply_number_rule`](crate::MineSweeper::apply_number_rule).
+/// The solvers and [`safe_cells`](crate::solver::safe_cells) only work with the [standard numbers](StandardNumbers),
+/// and [refuse](crate::MineSweeper::has_standard_numbers) any other board.
+pub trait NumberRule {
+    /// Returns the number shown on the safe cell at `coord`, which must be at most `8`.
+    /// `is_mine` tells whether any cell of the board is a mine.
+    fn number(
+        &mut self,
+        coord: Coordinate,
+        height: usize,
+        width: usize,
+        is_mine: &dyn Fn(Coordinate) -> bool,
+    ) -> u8;
+
+    /// If `true`, opening a cell showing a number with at least as many flagged neighbors
+    /// also opens its other neighbors, both when flooding empty areas and when chording.
+    /// If `false`, opening a cell never opens its neighbors.
+    ///
+    /// # Default
+    /// The default is `true`, which is right as long as the numbers are never smaller than the actual count.
+    const OPENS_NEIGHBORS: bool = true;
+
+    /// If `true`, each number is the count of the mines around the cell, as with [`StandardNumbers`],
+    /// so generators can skip applying the rule to boards that already have the standard numbers.
+    ///
+    /// # Default
+    /// The default is `false`, which is always right: the rule is then applied and the numbers are checked.
+    const STANDARD: bool = false;
+}
+
+/// The classic rule: each number is the count of the mines around the cell.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct StandardNumbers;
+
+impl NumberRule for StandardNumbers {
+    fn number(
+        &mut self,
+        coord: Coordinate,
+        height: usize,
+        width: usize,
+        is_mine: &dyn Fn(Coordinate) -> bool,
+    ) -> u8 {
+        neighbors(coord, height, width)
+            .filter(|&neighbor| is_mine(neighbor))
+            .count() as u8
+    }
+
+    const STANDARD: bool = true;
+}
+
+/// The rule of Liar minesweeper: each number is off by one from the count of the mines around the cell,
+/// randomly one more or one less.
+///
+/// Since no number can be trusted, opening a cell never opens its neighbors.
+#[derive(Debug, Clone)]
+pub struct LiarNumbers {
+    rng: Pcg32,
+}
+
+impl LiarNumbers {
+    pub fn new(seed: u64) -> Self {
+        LiarNumbers {
+            rng: Pcg32::new(seed),
+        }
+    }
+}
+
+impl NumberRule for LiarNumbers {
+    fn number(
+        &mut self,
+        coord: Coordinate,
+        height: usize,
+        width: usize,
+        is_mine: &dyn Fn(Coordinate) -> bool,
+    ) -> u8 {
+        match StandardNumbers.number(coord, height, width, is_mine) {
+            0 => 1,
+            8 => 7,
+            n if self.rng.below(2) == 0 => n - 1,
+            n => n + 1,
+        }
+    }
+
+    const OPENS_NEIGHBORS: bool = false;
+}
@@ -0,0 +1,73 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Difficulty, Error, MineSweeper, Move, Pcg32, Result};
+
+/// A game saved as its seed and the moves made by the player, without the position of the mines.
+///
+/// The board is rebuilt by [replaying](SavedGame::replay) the moves, placing the mines with a
+/// [`Pcg32`](Pcg32) initialized with the seed when the first cell is opened.
+/// The seed can be [removed](SavedGame::redacted) before handing the save to a client,
+/// so that only the server holding the seed can restore it and the client can't find the mines.
+///
+/// With the `serde` feature this can be serialized in any format, like JSON or a binary format.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SavedGame {
+    pub difficulty: Difficulty,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub seed: Option<u64>,
+    pub moves: Vec<Move>,
+}
+
+impl SavedGame {
+    /// Creates a save of a game with no moves.
+    pub fn new(difficulty: Difficulty, seed: u64) -> Self {
+        SavedGame {
+            difficulty,
+            seed: Some(seed),
+            moves: Vec::new(),
+        }
+    }
+
+    /// Returns a copy of the save without the seed.
+    pub fn redacted(&self) -> Self {
+        SavedGame {
+            seed: None,
+            ..self.clone()
+        }
+    }
+
+    /// Rebuilds the board on a new instance created with [`new_unstarted`](MineSweeper::new_unstarted).
+    ///
+    /// Returns [`MissingSeed`](Error::MissingSeed) if the save is redacted,
+    /// or the first error returned by a move.
+    pub fn restore<M: MineSweeper>(&self) -> Result<M> {
+        let mut ms = M::new_unstarted(self.difficulty)?;
+        self.replay(&mut ms)?;
+        Ok(ms)
+    }
+
+    /// Replays the moves on the given board, which must not be started yet.
+    /// Use this instead of [`restore`](SavedGame::restore) to choose how the board is created,
+    /// for example to use a [solver](crate::solver::Solver) with [`MSMatrix`](crate::MSMatrix).
+    pub fn replay(&self, ms: &mut impl MineSweeper) -> Result<()> {
+        let seed = self.seed.ok_or(Error::MissingSeed)?;
+        for &action in &self.moves {
+            place_mines_before(ms, seed, action)?;
+            action.play(ms)?;
+        }
+        Ok(())
+    }
+}
+
+/// Places the mines with the given seed if the move is the first open of the game.
+pub(crate) fn place_mines_before(ms: &mut impl MineSweeper, seed: u64, action: Move) -> Result<()> {
+    if let (Move::Open(coord), None) = (action, ms.started_from()) {
+        ms.place_mines_from_rng(coord, &mut Pcg32::new(seed))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use crate::{Coordinate, Difficulty, Error, Result};
+
+/// A setup that passed all the checks performed by [`validate_setup`].
+///
+/// It can only be obtained from [`validate_setup`], so a constructor that accepts it,
+/// like [`MineSweeper::from_setup`](crate::MineSweeper::from_setup),
+/// doesn't need to check its parameters again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ValidSetup {
+    difficulty: Difficulty,
+    start_from: Coordinate,
+}
+
+impl ValidSetup {
+    pub fn difficulty(&self) -> Difficulty {
+        self.difficulty
+    }
+
+    pub fn start_from(&self) -> Coordinate {
+        self.start_from
+    }
+}
+
+/// Checks the parameters of a new game, as done by the constructors of all the implementations.
+///
+/// Returns:
+/// - [`InvalidParameters`](Error::InvalidParameters) if the height or the width are `0`,
+///   or if the number of cells doesn't fit in a `usize`
+/// - [`TooManyMines`](Error::TooManyMines) if there are not at least `10` cells without mines
+///   (the starting cell with its neighbors, plus one)
+/// - [`OutOfBounds`](Error::OutOfBounds) if the starting cell is out of the board
+///
+/// ```
+/// # use mine_sweeperr::{validate_setup, Difficulty, Error};
+/// let setup = validate_setup(Difficulty::easy(), (0, 0)).unwrap();
+/// assert_eq!(setup.difficulty(), Difficulty::easy());
+/// assert_eq!(
+///     validate_setup((3, 3, 1).into(), (0, 0)),
+///     Err(Error::TooManyMines { mines: 1, max: 0 })
+/// );
+/// ```
+pub fn validate_setup(difficulty: Difficulty, start_from: Coordinate) -> Result<ValidSetup> {
+    validate_difficulty(difficulty)?;
+    let (height, width, _) = difficulty.into();
+    if start_from.0 >= height || start_from.1 >= width {
+        return Err(Error::OutOfBounds {
+            coord: start_from,
+            height,
+            width,
+        });
+    }
+    Ok(ValidSetup {
+        difficulty,
+        start_from,
+    })
+}
+
+/// Performs the checks of [`validate_setup`] that don't depend on the starting cell,
+/// as needed by [`new_unstarted`](crate::MineSweeper::new_unstarted).
+pub fn validate_difficulty(difficulty: Difficulty) -> Result<()> {
+    let (height, width, mines) = difficulty.into();
+    let cells = match height.checked_mul(width) {
+        Some(cells) if cells > 0 => cells,
+        _ => return Err(Error::InvalidParameters),
+    };
+    if mines.checked_add(9).map_or(true, |needed| needed >= cells) {
+        return Err(Error::TooManyMines {
+            mines,
+            max: cells.saturating_sub(10),
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,472 @@
+//! Plays many games automatically and collects statistics about them.
+//!
+//! This can be used to check empirically how the [rules](GameRules) and the difficulty
+//! change the outcome of the games, or to compare [strategies](Strategy).
+//!
+//! ```
+//! use mine_sweeperr::{
+//!     simulate::{RandomStrategy, Simulation},
+//!     Difficulty, MSMatrix,
+//! };
+//!
+//! let statistics = Simulation::new(Difficulty::easy(), 100).run::<MSMatrix, _>(RandomStrategy::new);
+//! assert_eq!(statistics.games, 100);
+//! assert_eq!(statistics.first_click_losses, 0);
+//! ```
+
+use std::{
+    fmt::{Display, Formatter},
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    solver::Solver, CellView, Coordinate, Difficulty, Game, GameRules, MineSweeper, MineSweeperExt,
+    Move, Pcg32, Random, SpectatorView,
+};
+
+/// Chooses the moves of a simulated player, only knowing what the player can see.
+pub trait Strategy<M: MineSweeper> {
+    /// Returns the next move, or `None` to give up the game.
+    fn next_move(&mut self, view: SpectatorView<'_, M>) -> Option<Move>;
+    /// Returns the number of cells opened so far without knowing they were safe,
+    /// like [`Solver::guessed`], or `None` if the strategy doesn't count its guesses.
+    ///
+    /// # Default
+    /// The default implementation returns `None`.
+    fn guessed(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// Opens a random closed cell at each move.
+#[derive(Debug, Clone)]
+pub struct RandomStrategy {
+    rng: Pcg32,
+    guessed: usize,
+}
+
+impl RandomStrategy {
+    pub fn new(seed: u64) -> Self {
+        RandomStrategy {
+            rng: Pcg32::new(seed),
+            guessed: 0,
+        }
+    }
+}
+
+impl<M: MineSweeper> Strategy<M> for RandomStrategy {
+    fn next_move(&mut self, view: SpectatorView<'_, M>) -> Option<Move> {
+        let cells = view.board_view().cells;
+        let closed: Vec<_> = (0..cells.len())
+            .filter(|&i| cells[i] == CellView::Closed)
+            .collect();
+        if closed.is_empty() {
+            return None;
+        }
+        let index = closed[self.rng.below(closed.len())];
+        self.guessed += 1;
+        Some(Move::Open((index / view.width(), index % view.width())))
+    }
+
+    /// Every move is a guess.
+    fn guessed(&self) -> Option<usize> {
+        Some(self.guessed)
+    }
+}
+
+/// The outcome of a single simulated game.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Outcome {
+    pub won: bool,
+    pub lost: bool,
+    /// The game was lost at the first move.
+    pub lost_on_first_click: bool,
+    pub cells_opened: usize,
+    /// The number of successful moves.
+    pub moves: u64,
+    /// The number of guesses made by the strategy, as returned by [`Strategy::guessed`].
+    pub guesses: Option<usize>,
+}
+
+/// Statistics about a set of simulated games.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Statistics {
+    pub games: usize,
+    pub won: usize,
+    pub lost: usize,
+    pub first_click_losses: usize,
+    pub cells_opened: usize,
+    pub moves: u64,
+    /// The total number of guesses, `None` if the strategy doesn't count them.
+    pub guesses: Option<usize>,
+}
+
+impl Statistics {
+    /// Adds the outcome of a game.
+    pub fn add(&mut self, outcome: Outcome) {
+        self.guesses = if self.games == 0 {
+            outcome.guesses
+        } else {
+            add_guesses(self.guesses, outcome.guesses)
+        };
+        self.games += 1;
+        self.won += outcome.won as usize;
+        self.lost += outcome.lost as usize;
+        self.first_click_losses += outcome.lost_on_first_click as usize;
+        self.cells_opened += outcome.cells_opened;
+        self.moves += outcome.moves;
+    }
+
+    /// Merges the statistics of two sets of games.
+    pub fn merge(mut self, other: Statistics) -> Self {
+        self.guesses = match (self.games, other.games) {
+            (0, _) => other.guesses,
+            (_, 0) => self.guesses,
+            _ => add_guesses(self.guesses, other.guesses),
+        };
+        self.games += other.games;
+        self.won += other.won;
+        self.lost += other.lost;
+        self.first_click_losses += other.first_click_losses;
+        self.cells_opened += other.cells_opened;
+        self.moves += other.moves;
+        self
+    }
+
+    pub fn win_rate(&self) -> f64 {
+        self.won as f64 / self.games as f64
+    }
+
+    pub fn first_click_loss_rate(&self) -> f64 {
+        self.first_click_losses as f64 / self.games as f64
+    }
+
+    pub fn average_cells_opened(&self) -> f64 {
+        self.cells_opened as f64 / self.games as f64
+    }
+}
+
+/// Adds two numbers of guesses, which are not counted if one of them is not.
+fn add_guesses(a: Option<usize>, b: Option<usize>) -> Option<usize> {
+    a.zip(b).map(|(a, b)| a.saturating_add(b))
+}
+
+impl FromIterator<Outcome> for Statistics {
+    fn from_iter<T: IntoIterator<Item = Outcome>>(iter: T) -> Self {
+        let mut statistics = Statistics::default();
+        iter.into_iter().for_each(|outcome| statistics.add(outcome));
+        statistics
+    }
+}
+
+/// The parameters of a set of simulated games.
+///
+/// The games are numbered from `0` and each one is fully determined by its number and the seed,
+/// so simulations can be repeated and split between threads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Simulation {
+    pub difficulty: Difficulty,
+    pub rules: GameRules,
+    /// The number of games to play.
+    pub games: usize,
+    pub seed: u64,
+    /// The number of moves after which a game is abandoned.
+    pub max_moves: u64,
+}
+
+impl Simulation {
+    /// Creates a simulation with the [default rules](GameRules::default),
+    /// seed `0` and enough moves to open each cell twice.
+    pub fn new(difficulty: Difficulty, games: usize) -> Self {
+        Simulation {
+            difficulty,
+            rules: GameRules::default(),
+            games,
+            seed: 0,
+            max_moves: 2 * (difficulty.height() * difficulty.width()) as u64,
+        }
+    }
+
+    /// Plays all the games, creating a strategy for each game from its seed.
+    ///
+    /// # Panics
+    /// Panics if the difficulty is not [valid](crate::validate_difficulty).
+    pub fn run<M: MineSweeper, S: Strategy<M>>(
+        &self,
+        new_strategy: impl Fn(u64) -> S,
+    ) -> Statistics {
+        (0..self.games)
+            .map(|game| self.play::<M, S>(game, &new_strategy))
+            .collect()
+    }
+
+    /// Plays all the games in parallel on the [rayon] thread pool,
+    /// with the same results as [`run`](Self::run).
+    ///
+    /// # Panics
+    /// Panics if the difficulty is not [valid](crate::validate_difficulty).
+    #[cfg(feature = "rayon")]
+    pub fn run_parallel<M: MineSweeper, S: Strategy<M>>(
+        &self,
+        new_strategy: impl Fn(u64) -> S + Sync,
+    ) -> Statistics {
+        (0..self.games)
+            .into_par_iter()
+            .fold(Statistics::default, |mut statistics, game| {
+                statistics.add(self.play::<M, S>(game, &new_strategy));
+                statistics
+            })
+            .reduce(Statistics::default, Statistics::merge)
+    }
+
+    /// Plays the game with the given number.
+    ///
+    /// # Panics
+    /// Panics if the difficulty is not [valid](crate::validate_difficulty).
+    pub fn play<M: MineSweeper, S: Strategy<M>>(
+        &self,
+        game: usize,
+        new_strategy: impl Fn(u64) -> S,
+    ) -> Outcome {
+        let seed = self.seed.wrapping_add(game as u64);
+        let mut strategy = new_strategy(!seed);
+        let board = M::new_unstarted(self.difficulty).expect("invalid difficulty");
+        let Some(first) = strategy.next_move(SpectatorView::new(&board)) else {
+            return Outcome {
+                guesses: strategy.guessed(),
+                ..Outcome::default()
+            };
+        };
+        self.play_on(board, seed, &mut strategy, first)
+    }
+
+    /// Plays a game starting with the given move.
+    /// If the mines are not placed yet, they are placed at the first open with the given seed,
+    /// whichever move it is.
+    fn play_on<M: MineSweeper, S: Strategy<M>>(
+        &self,
+        board: M,
+        seed: u64,
+        strategy: &mut S,
+        first: Move,
+    ) -> Outcome {
+        let mut game = Game::builder(board).rules(self.rules).seed(seed).build();
+        let mut next = Some(first);
+        for _ in 0..self.max_moves {
+            let Some(action) = next else {
+                break;
+            };
+            // invalid moves are not counted but still take one of the available moves
+            let _ = game.play(action);
+            if game.is_lost() || game.board().is_cleared() {
+                break;
+            }
+            next = strategy.next_move(game.spectator());
+        }
+        Outcome {
+            won: game.board().is_cleared() && !game.is_lost(),
+            lost: game.is_lost(),
+            lost_on_first_click: game.is_lost() && game.moves() == 1,
+            cells_opened: game.board().get_game_state().opened,
+            moves: game.moves(),
+            guesses: strategy.guessed(),
+        }
+    }
+}
+
+/// The results of a solver or a strategy in a [`Report`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ReportEntry {
+    pub name: String,
+    /// The number of boards solved by the solver or games won by the strategy.
+    pub won: usize,
+    pub win_rate: f64,
+    /// The total number of guesses, as returned by [`Solver::guessed`] or [`Strategy::guessed`].
+    /// It is `None` if the entry doesn't count its guesses.
+    pub guesses: Option<usize>,
+    pub guesses_per_board: Option<f64>,
+    /// The average time spent solving or playing a board, without generating it.
+    pub time_per_board: Duration,
+}
+
+/// The results of a [`Comparison`].
+/// With the `serde` feature the report can be serialized, for example to JSON.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Report {
+    pub difficulty: Difficulty,
+    pub seed: u64,
+    pub games: usize,
+    pub entries: Vec<ReportEntry>,
+}
+
+/// Compares solvers and strategies on the same boards.
+///
+/// The boards are generated once by [`new`](Comparison::new):
+/// board `i` is created by [`MineSweeper::from_seed`] with seed `simulation.seed + i`
+/// and starts from the center, so each entry is measured on exactly the same boards.
+/// Strategies play each board with the [rules](GameRules) of the simulation,
+/// after opening the center as their first move.
+///
+/// ```
+/// use mine_sweeperr::{
+///     simulate::{Comparison, RandomStrategy, Simulation},
+///     solver::CSPSolver,
+///     Difficulty, MSMatrix,
+/// };
+///
+/// let mut comparison = Comparison::<MSMatrix>::new(Simulation::new(Difficulty::easy(), 20));
+/// comparison
+///     .add_solver::<CSPSolver>("csp")
+///     .add_strategy("random", RandomStrategy::new);
+/// let report = comparison.report();
+/// assert!(report.entries[0].win_rate > report.entries[1].win_rate);
+/// println!("{report}");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Comparison<M> {
+    simulation: Simulation,
+    start_from: Coordinate,
+    boards: Vec<M>,
+    report: Report,
+}
+
+impl<M: MineSweeper + Clone> Comparison<M> {
+    /// Generates the boards of the given simulation.
+    ///
+    /// # Panics
+    /// Panics if the difficulty is not [valid](crate::validate_difficulty).
+    pub fn new(simulation: Simulation) -> Self {
+        let difficulty = simulation.difficulty;
+        let start_from = (difficulty.height() / 2, difficulty.width() / 2);
+        let boards = (0..simulation.games)
+            .map(|game| {
+                let seed = simulation.seed.wrapping_add(game as u64);
+                M::from_seed(difficulty, start_from, seed).expect("invalid difficulty")
+            })
+            .collect();
+        Comparison {
+            simulation,
+            start_from,
+            boards,
+            report: Report {
+                difficulty,
+                seed: simulation.seed,
+                games: simulation.games,
+                entries: Vec::new(),
+            },
+        }
+    }
+
+    /// The boards on which the entries are measured.
+    pub fn boards(&self) -> &[M] {
+        &self.boards
+    }
+
+    /// Adds the results of a solver.
+    pub fn add_solver<S: Solver<M>>(&mut self, name: &str) -> &mut Self {
+        let (mut won, mut guesses, mut elapsed) = (0, Some(0), Duration::ZERO);
+        for ms in &self.boards {
+            let time = Instant::now();
+            let mut solver = S::new(ms);
+            won += solver.solve(self.start_from) as usize;
+            elapsed += time.elapsed();
+            // solvers return `usize::MAX` if they don't count their guesses
+            let guessed = Some(solver.guessed()).filter(|&guessed| guessed != usize::MAX);
+            guesses = add_guesses(guesses, guessed);
+        }
+        self.push(name, won, guesses, elapsed)
+    }
+
+    /// Adds the results of a strategy, creating it from the seed of each game
+    /// as in [`Simulation::play`].
+    pub fn add_strategy<S: Strategy<M>>(
+        &mut self,
+        name: &str,
+        new_strategy: impl Fn(u64) -> S,
+    ) -> &mut Self {
+        let (mut won, mut guesses, mut elapsed) = (0, Some(0), Duration::ZERO);
+        for (game, ms) in self.boards.iter().enumerate() {
+            let seed = self.simulation.seed.wrapping_add(game as u64);
+            let mut strategy = new_strategy(!seed);
+            let board = ms.clone();
+            let time = Instant::now();
+            let outcome =
+                self.simulation
+                    .play_on(board, seed, &mut strategy, Move::Open(self.start_from));
+            elapsed += time.elapsed();
+            won += outcome.won as usize;
+            guesses = add_guesses(guesses, outcome.guesses);
+        }
+        self.push(name, won, guesses, elapsed)
+    }
+
+    /// The results of the entries added so far.
+    pub fn report(&self) -> &Report {
+        &self.report
+    }
+
+    pub fn into_report(self) -> Report {
+        self.report
+    }
+
+    fn push(
+        &mut self,
+        name: &str,
+        won: usize,
+        guesses: Option<usize>,
+        elapsed: Duration,
+    ) -> &mut Self {
+        let games = self.report.games.max(1);
+        self.report.entries.push(ReportEntry {
+            name: name.to_string(),
+            won,
+            win_rate: won as f64 / games as f64,
+            guesses,
+            guesses_per_board: guesses.map(|guesses| guesses as f64 / games as f64),
+            time_per_board: elapsed.div_f64(games as f64),
+        });
+        self
+    }
+}
+
+impl Display for Report {
+    /// Formats the report as a table, one entry per line.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{}x{} with {} mines, {} boards from seed {}",
+            self.difficulty.height(),
+            self.difficulty.width(),
+            self.difficulty.mines(),
+            self.games,
+            self.seed
+        )?;
+        writeln!(
+            f,
+            "{:<20} {:>8} {:>8} {:>12}",
+            "name", "win rate", "guesses", "time"
+        )?;
+        for entry in &self.entries {
+            // the entries that don't count their guesses have no value in the column
+            let guesses = entry
+                .guesses_per_board
+                .map_or("-".to_string(), |guesses| format!("{guesses:.2}"));
+            writeln!(
+                f,
+                "{:<20} {:>7.2}% {:>8} {:>12?}",
+                entry.name,
+                entry.win_rate * 100.0,
+                guesses,
+                entry.time_per_board
+            )?;
+        }
+        Ok(())
+    }
+}
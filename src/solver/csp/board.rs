@@ -1,7 +1,7 @@
-use std::{cell::RefCell, fmt::Display, rc::Rc};
+use std::{cell::RefCell, fmt::Display, mem::size_of, rc::Rc};
 
-use super::Constraint;
-use crate::{iter_neighbors, Cell, CellContent, CellState, Coordinate, MineSweeper};
+use super::{rc_allocation_size, Constraint};
+use crate::{neighbors, Cell, CellContent, CellState, Coordinate, MineSweeper};
 
 pub(crate) const UNKNOWN: isize = -5;
 pub(crate) const CONSTRAINED: isize = -4;
@@ -80,6 +80,20 @@ impl Board {
         }
     }
 
+    /// Returns an estimate of the memory used by the board, in bytes.
+    pub fn memory_footprint(&self) -> usize {
+        size_of::<Self>()
+            + self.cells.capacity() * size_of::<Vec<Rc<RefCell<BoardCell>>>>()
+            + self
+                .cells
+                .iter()
+                .map(|row| {
+                    row.capacity() * size_of::<Rc<RefCell<BoardCell>>>()
+                        + row.len() * rc_allocation_size::<BoardCell>()
+                })
+                .sum::<usize>()
+    }
+
     pub fn enumerate_boundary(&mut self, level: isize) -> Vec<Rc<RefCell<BoardCell>>> {
         // let mut result = Vec::with_capacity(self.unknown as isize);
         // for row in &self.cells {
@@ -202,7 +216,7 @@ impl Board {
         }
         let mut constraint = Constraint::new();
         let mut constant = self.cells[r][c].borrow().state;
-        for (r, c) in iter_neighbors(coord, self.cells.len(), self.cells[0].len()).unwrap() {
+        for (r, c) in neighbors(coord, self.cells.len(), self.cells[0].len()) {
             if self.cells[r][c].borrow().state < 0 {
                 if self.cells[r][c].borrow().state == MARKED {
                     constant -= 1;
@@ -266,8 +280,7 @@ impl Board {
             CONSTRAINED => {
                 // self.constrained += 1;
                 cell.boundary_level = 0;
-                for (r, c) in iter_neighbors(coord, self.cells.len(), self.cells[0].len()).unwrap()
-                {
+                for (r, c) in neighbors(coord, self.cells.len(), self.cells[0].len()) {
                     if self.cells[r][c].borrow().state == UNKNOWN {
                         self.cells[r][c].borrow_mut().boundary_level += 1;
                     }
@@ -276,8 +289,7 @@ impl Board {
             MARKED => {
                 // self.mine += 1;
                 cell.boundary_level = 0;
-                for (r, c) in iter_neighbors(coord, self.cells.len(), self.cells[0].len()).unwrap()
-                {
+                for (r, c) in neighbors(coord, self.cells.len(), self.cells[0].len()) {
                     if self.cells[r][c].borrow().state == UNKNOWN {
                         self.cells[r][c].borrow_mut().boundary_level -= 1;
                     }
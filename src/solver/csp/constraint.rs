@@ -1,11 +1,18 @@
 #[cfg(test)]
 use std::fmt::{Debug, Display, Formatter};
-use std::{borrow::BorrowMut, cell::RefCell, rc::Rc};
+use std::{borrow::BorrowMut, cell::RefCell, mem::size_of, rc::Rc};
 
 use super::board::{Board, BoardCell, MARKED};
+use crate::Coordinate;
+
+/// The variables of a constraint are the closed neighbors of a cell, so they are at most 8.
+#[cfg(feature = "smallvec")]
+type Variables = smallvec::SmallVec<[Rc<RefCell<BoardCell>>; 8]>;
+#[cfg(not(feature = "smallvec"))]
+type Variables = Vec<Rc<RefCell<BoardCell>>>;
 
 pub(crate) struct Constraint {
-    variables: Vec<Rc<RefCell<BoardCell>>>,
+    variables: Variables,
     pub constant: isize,
     unassigned: isize,
     current_constant: isize,
@@ -15,7 +22,7 @@ pub(crate) struct Constraint {
 impl Constraint {
     pub fn new() -> Self {
         Constraint {
-            variables: Vec::with_capacity(8),
+            variables: Variables::with_capacity(8),
             constant: 0,
             // nvariables: 0,
             unassigned: 0,
@@ -32,6 +39,13 @@ impl Constraint {
         self.variables.iter().map(Rc::clone).collect()
     }
 
+    /// Returns the coordinates of the variables.
+    pub fn coordinates(&self) -> impl Iterator<Item = Coordinate> + '_ {
+        self.variables
+            .iter()
+            .map(|variable| variable.borrow().coordinate)
+    }
+
     pub fn set_constant(&mut self, constant: isize) {
         self.constant = constant;
     }
@@ -157,6 +171,20 @@ impl Constraint {
         false
     }
 
+    /// Returns the number of bytes allocated on the heap by the constraint,
+    /// not counting the cells it refers to.
+    pub fn memory_footprint(&self) -> usize {
+        #[cfg(feature = "smallvec")]
+        let on_heap = self.variables.spilled();
+        #[cfg(not(feature = "smallvec"))]
+        let on_heap = true;
+        if on_heap {
+            self.variables.capacity() * size_of::<Rc<RefCell<BoardCell>>>()
+        } else {
+            0
+        }
+    }
+
     pub fn is_empty(&self) -> bool {
         self.variables.is_empty()
     }
@@ -1,25 +1,71 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, collections::HashMap, mem::size_of, rc::Rc};
 
 use board::Board;
 use constraint::Constraint;
+pub use safe_cells::safe_cells;
 
 use super::{csp::solution_set::SolutionSet, Solver};
 use crate::{solver::csp::board::MINE, Coordinate, MineSweeper};
 
+/// Returns the size of the heap allocation made by [`Rc::new`](Rc::new) for a `RefCell<T>`,
+/// which also holds the strong and weak counters.
+pub(crate) fn rc_allocation_size<T>() -> usize {
+    2 * size_of::<usize>() + size_of::<RefCell<T>>()
+}
+
 mod board;
 mod constraint;
+mod safe_cells;
 mod solution_set;
 #[cfg(test)]
 mod tests;
 
+/// The default maximum number of coupled variables that a single group of constraints
+/// is allowed to have to be solved exactly.
+pub const DEFAULT_MAX_EXACT_VARIABLES: usize = 40;
+
 pub struct CSPSolver {
     constraints: Vec<Rc<RefCell<Constraint>>>,
     board: Board,
+    max_exact_variables: usize,
+    unsolvable_clusters: Vec<Vec<Coordinate>>,
+    /// Boards with other [numbers](crate::NumberRule) are never solved.
+    standard_numbers: bool,
 }
 
 impl CSPSolver {
+    /// Sets the maximum number of coupled variables that a single group of constraints
+    /// is allowed to have to be solved exactly.
+    ///
+    /// Enumerating the solutions of a group takes exponential time in the number of its variables,
+    /// so bigger groups are only bounded approximately and never used to deduce mines.
+    /// This prevents a single pathological border from dominating the solving time,
+    /// at the cost of failing to solve some boards.
+    /// Defaults to [`DEFAULT_MAX_EXACT_VARIABLES`](DEFAULT_MAX_EXACT_VARIABLES).
+    pub fn with_max_exact_variables(mut self, max_exact_variables: usize) -> Self {
+        self.max_exact_variables = max_exact_variables;
+        self
+    }
+
+    /// Returns an estimate of the memory used by this solver, in bytes.
+    ///
+    /// The estimate includes the internal copy of the board and all the constraints
+    /// collected so far, so it grows while [`solve`](Solver::solve) runs.
+    pub fn memory_footprint(&self) -> usize {
+        size_of::<Self>()
+            + self.board.memory_footprint()
+            + self.constraints.capacity() * size_of::<Rc<RefCell<Constraint>>>()
+            + self
+                .constraints
+                .iter()
+                .map(|constraint| {
+                    rc_allocation_size::<Constraint>() + constraint.borrow().memory_footprint()
+                })
+                .sum::<usize>()
+    }
+
     fn solve(&mut self, start_from: Coordinate) -> bool {
-        if self.board.open(start_from) == MINE {
+        if !self.standard_numbers || self.board.open(start_from) == MINE {
             return false;
         }
         for i in 0..self.board.cells.len() {
@@ -37,12 +83,12 @@ impl CSPSolver {
             let mut subsets = self.separate_constraints();
             if !subsets.is_empty() {
                 for subset in &mut subsets {
-                    subset.enumerate_solutions();
+                    subset.enumerate_solutions(self.max_exact_variables);
                 }
             }
             let remaining = self.board.unflagged_mines();
             let far = self.board.unknown;
-            let mut far_max = remaining as isize;
+            let mut far_max = remaining;
             for i in 0..subsets.len() {
                 let (mut min, mut max) = (0, far as isize);
                 for (j, subset) in subsets.iter().enumerate() {
@@ -52,7 +98,7 @@ impl CSPSolver {
                     }
                 }
                 subsets[i].reduce_min_max(remaining - max, remaining - min);
-                far_max -= subsets[i].get_min() as isize;
+                far_max -= subsets[i].get_min();
             }
             for subset in subsets {
                 subset.mark_mines(&mut self.board);
@@ -130,15 +176,49 @@ impl CSPSolver {
             if !done {
                 continue;
             }
+            // Two constraints can only simplify each other if they share at least one variable,
+            // so only the pairs found through the index of variables are compared.
+            // Simplifying never adds variables, so the index can't miss any pair while it gets stale.
+            // Constraints are identified by their position at the beginning of the pass,
+            // since empty constraints are removed (and others moved) while iterating.
+            let index = self.index_variables();
+            let mut ids: Vec<usize> = (0..self.constraints.len()).collect();
+            let mut positions = ids.clone();
+            let mut candidates = Vec::new();
             let mut i = 0;
             while i < self.constraints.len() {
                 while i < self.constraints.len()
                     && <RefCell<_>>::borrow(&self.constraints[i]).is_empty()
                 {
                     self.constraints.swap_remove(i);
+                    positions[ids.swap_remove(i)] = usize::MAX;
+                    if i < ids.len() {
+                        positions[ids[i]] = i;
+                    }
                 }
                 if i < self.constraints.len() {
-                    for j in i + 1..self.constraints.len() {
+                    // An empty constraint is a subset of any other one.
+                    // Other constraints only become empty after a successful simplification,
+                    // so this check is needed only once per pass.
+                    if i == 0
+                        && self.constraints[1..]
+                            .iter()
+                            .any(|constraint| <RefCell<_>>::borrow(constraint).is_empty())
+                    {
+                        done = false;
+                    }
+                    candidates.clear();
+                    for coordinate in <RefCell<_>>::borrow(&self.constraints[i]).coordinates() {
+                        candidates.extend(
+                            index[&coordinate]
+                                .iter()
+                                .map(|&id| positions[id])
+                                .filter(|&j| j > i && j != usize::MAX),
+                        );
+                    }
+                    candidates.sort_unstable();
+                    candidates.dedup();
+                    for &j in &candidates {
                         if Constraint::simplify(
                             Rc::clone(&self.constraints[i]),
                             Rc::clone(&self.constraints[j]),
@@ -156,54 +236,61 @@ impl CSPSolver {
     }
 }
 
+impl CSPSolver {
+    /// Maps the coordinate of each variable to the indexes of the constraints containing it.
+    fn index_variables(&self) -> HashMap<Coordinate, Vec<usize>> {
+        let mut result: HashMap<_, Vec<_>> = HashMap::with_capacity(self.constraints.len() * 2);
+        for (i, constraint) in self.constraints.iter().enumerate() {
+            for coordinate in <RefCell<_>>::borrow(constraint).coordinates() {
+                result.entry(coordinate).or_default().push(i);
+            }
+        }
+        result
+    }
+}
+
 impl<M: MineSweeper> Solver<M> for CSPSolver {
     fn new(ms: &M) -> Self {
         let board = Board::new(ms);
         CSPSolver {
             constraints: Vec::with_capacity(ms.width() * ms.height()),
             board,
+            max_exact_variables: DEFAULT_MAX_EXACT_VARIABLES,
+            unsolvable_clusters: Vec::new(),
+            standard_numbers: ms.has_standard_numbers(),
         }
     }
 
     fn solve(&mut self, start_from: Coordinate) -> bool {
         let result = self.solve(start_from);
-        // #[cfg(test)]
-        // {
-        //     // println!(
-        //     //     "{}",
-        //     //     this.constraints
-        //     //         .iter()
-        //     //         .map(|r| r.borrow().to_string())
-        //     //         .collect::<Vec<_>>()
-        //     //         .join("\n")
-        //     // );
-        //     let sets = this.separate_constraints();
-        //     println!(
-        //         "[{}]",
-        //         sets.iter()
-        //             .map(ToString::to_string)
-        //             .collect::<Vec<_>>()
-        //             .join(",\n\n")
-        //     );
-        // }
+        if !result {
+            self.unsolvable_clusters = self
+                .separate_constraints()
+                .iter()
+                .map(|set| {
+                    let mut result: Vec<_> = set
+                        .get_variables()
+                        .iter()
+                        .map(|v| v.borrow().coordinate)
+                        .collect();
+                    result.sort();
+                    result.dedup();
+                    result
+                })
+                .filter(|cluster| !cluster.is_empty())
+                .collect();
+        }
         result
-        // if result {
-        //     Ok(())
-        // } else {
-        //     Err(this
-        //         .separate_constraints()
-        //         .iter()
-        //         .map(|set| {
-        //             let mut result: Vec<_> = set
-        //                 .get_variables()
-        //                 .iter()
-        //                 .map(|v| v.borrow().coordinate)
-        //                 .collect();
-        //             result.sort();
-        //             result.dedup();
-        //             result
-        //         })
-        //         .collect())
-        // }
+    }
+
+    /// The solver never guesses: it gives up instead.
+    fn guessed(&self) -> usize {
+        0
+    }
+
+    /// Returns the coordinates of the variables of each group of constraints
+    /// left unsolved by the last call to [`solve`](Solver::solve).
+    fn get_unsolvable_clusters(&self) -> Vec<Vec<Coordinate>> {
+        self.unsolvable_clusters.clone()
     }
 }
@@ -0,0 +1,214 @@
+use std::collections::VecDeque;
+
+use crate::{neighbors, CellContent, CellState, Coordinate, MineSweeper};
+
+/// Returns the closed cells that can't be mines according to what a player can see:
+/// the open numbers and the total number of mines.
+///
+/// Unlike [`solve`](crate::solver::Solver::solve), this only deduces from the open cells,
+/// so it can be used on a position in the middle of a game.
+/// Flags are ignored, since they may be wrong, while opened mines count as mines.
+/// If the position is inconsistent or the board doesn't have the
+/// [standard numbers](MineSweeper::has_standard_numbers), no cell is returned.
+///
+/// Each group of closed cells sharing the same numbers is enumerated exactly,
+/// which takes exponential time in the size of the group.
+pub fn safe_cells(ms: &impl MineSweeper) -> Vec<Coordinate> {
+    if !ms.has_standard_numbers() {
+        return Vec::new();
+    }
+    let (height, width) = (ms.height(), ms.width());
+    let cells = ms.cells_snapshot();
+    let coord = |i: usize| (i / width, i % width);
+    let is_closed = |i: usize| cells[i].state != CellState::Open;
+    let exploded = cells
+        .iter()
+        .filter(|cell| cell.state == CellState::Open && cell.content == CellContent::Mine)
+        .count();
+    let Some(mines) = ms.mines().checked_sub(exploded) else {
+        return Vec::new();
+    };
+
+    // one constraint for each open number next to closed cells
+    let mut constraints = Vec::new();
+    let mut constraints_of = vec![Vec::new(); cells.len()];
+    for (i, cell) in cells.iter().enumerate() {
+        let CellContent::Number(n) = cell.content else {
+            continue;
+        };
+        if is_closed(i) {
+            continue;
+        }
+        let (mut closed, mut known) = (Vec::new(), 0);
+        for (r, c) in neighbors(coord(i), height, width) {
+            let j = r * width + c;
+            if is_closed(j) {
+                closed.push(j);
+            } else if cells[j].content == CellContent::Mine {
+                known += 1;
+            }
+        }
+        let Some(n) = (n as usize).checked_sub(known) else {
+            return Vec::new();
+        };
+        if !closed.is_empty() {
+            for &j in &closed {
+                constraints_of[j].push(constraints.len());
+            }
+            constraints.push((closed, n));
+        }
+    }
+
+    // the groups of closed cells linked by the constraints, each solved on its own
+    let mut group_of = vec![usize::MAX; cells.len()];
+    let mut groups = Vec::new();
+    for i in (0..cells.len()).filter(|&i| !constraints_of[i].is_empty()) {
+        if group_of[i] != usize::MAX {
+            continue;
+        }
+        let mut group = Vec::new();
+        group_of[i] = groups.len();
+        let mut queue = VecDeque::from([i]);
+        while let Some(j) = queue.pop_front() {
+            group.push(j);
+            for &constraint in &constraints_of[j] {
+                for &k in &constraints[constraint].0 {
+                    if group_of[k] == usize::MAX {
+                        group_of[k] = groups.len();
+                        queue.push_back(k);
+                    }
+                }
+            }
+        }
+        groups.push(group);
+    }
+    let unconstrained = (0..cells.len())
+        .filter(|&i| is_closed(i) && constraints_of[i].is_empty())
+        .count();
+
+    // for each group and number of mines in it, which of its cells can be mines
+    let solutions: Vec<_> = groups
+        .iter()
+        .map(|group| Enumeration::new(group, &constraints, &constraints_of).run())
+        .collect();
+    // the possible numbers of mines in all the groups but one
+    let possible_totals = |skip: usize| {
+        let mut totals = vec![true];
+        for (_, group) in solutions.iter().enumerate().filter(|&(g, _)| g != skip) {
+            let mut next = vec![false; totals.len() + group.len() - 1];
+            for t in (0..totals.len()).filter(|&t| totals[t]) {
+                for k in (0..group.len()).filter(|&k| group[k].is_some()) {
+                    next[t + k] = true;
+                }
+            }
+            totals = next;
+        }
+        totals
+    };
+    let fits = |total: usize| total <= mines && mines - total <= unconstrained;
+
+    let mut can_be_mine = vec![false; cells.len()];
+    for (g, group) in groups.iter().enumerate() {
+        let others = possible_totals(g);
+        for (k, solution) in solutions[g].iter().enumerate() {
+            let Some(solution) = solution else {
+                continue;
+            };
+            if others.iter().enumerate().any(|(t, &p)| p && fits(k + t)) {
+                for (&i, &mine) in group.iter().zip(solution) {
+                    can_be_mine[i] |= mine;
+                }
+            }
+        }
+    }
+    let totals: Vec<_> = possible_totals(usize::MAX)
+        .into_iter()
+        .enumerate()
+        .filter(|&(total, p)| p && fits(total))
+        .map(|(total, _)| total)
+        .collect();
+    if totals.is_empty() {
+        return Vec::new();
+    }
+    if totals.iter().any(|&total| total < mines) {
+        for i in (0..cells.len()).filter(|&i| is_closed(i) && constraints_of[i].is_empty()) {
+            can_be_mine[i] = true;
+        }
+    }
+    (0..cells.len())
+        .filter(|&i| is_closed(i) && !can_be_mine[i])
+        .map(coord)
+        .collect()
+}
+
+/// Enumerates the assignments of mines to a group of cells that satisfy all their constraints.
+struct Enumeration<'a> {
+    group: &'a [usize],
+    constraints: &'a [(Vec<usize>, usize)],
+    constraints_of: &'a [Vec<usize>],
+    /// For each constraint, the mines assigned so far and the cells not assigned yet.
+    state: Vec<(usize, usize)>,
+    assignment: Vec<bool>,
+    /// For each number of mines, which cells are mines in at least one solution.
+    solutions: Vec<Option<Vec<bool>>>,
+}
+
+impl<'a> Enumeration<'a> {
+    fn new(
+        group: &'a [usize],
+        constraints: &'a [(Vec<usize>, usize)],
+        constraints_of: &'a [Vec<usize>],
+    ) -> Self {
+        Enumeration {
+            group,
+            constraints,
+            constraints_of,
+            state: constraints
+                .iter()
+                .map(|(cells, _)| (0, cells.len()))
+                .collect(),
+            assignment: Vec::with_capacity(group.len()),
+            solutions: vec![None; group.len() + 1],
+        }
+    }
+
+    fn run(mut self) -> Vec<Option<Vec<bool>>> {
+        self.assign(0);
+        self.solutions
+    }
+
+    fn assign(&mut self, mines: usize) {
+        let Some(&cell) = self.group.get(self.assignment.len()) else {
+            let solution =
+                self.solutions[mines].get_or_insert_with(|| vec![false; self.group.len()]);
+            for (seen, &mine) in solution.iter_mut().zip(&self.assignment) {
+                *seen |= mine;
+            }
+            return;
+        };
+        for mine in [false, true] {
+            let constraints_of = self.constraints_of;
+            // each constraint must still be reachable once the cell is assigned
+            let feasible = constraints_of[cell].iter().all(|&constraint| {
+                let (assigned, unassigned) = self.state[constraint];
+                let (assigned, unassigned) = (assigned + mine as usize, unassigned - 1);
+                let target = self.constraints[constraint].1;
+                assigned <= target && assigned + unassigned >= target
+            });
+            if !feasible {
+                continue;
+            }
+            for &constraint in &constraints_of[cell] {
+                self.state[constraint].0 += mine as usize;
+                self.state[constraint].1 -= 1;
+            }
+            self.assignment.push(mine);
+            self.assign(mines + mine as usize);
+            self.assignment.pop();
+            for &constraint in &constraints_of[cell] {
+                self.state[constraint].0 -= mine as usize;
+                self.state[constraint].1 += 1;
+            }
+        }
+    }
+}
@@ -8,6 +8,8 @@ use crate::solver::csp::{
 };
 
 pub(crate) struct SolutionSet {
+    /// `false` if the set was too big to be enumerated and only holds approximated bounds.
+    exact: bool,
     constraints: Vec<Rc<RefCell<Constraint>>>,
     variables: Vec<Rc<RefCell<BoardCell>>>,
     nodes: Vec<ConstraintList>,
@@ -21,6 +23,7 @@ impl SolutionSet {
     pub fn new(constraints: Vec<Rc<RefCell<Constraint>>>) -> Self {
         let nodes = Vec::with_capacity(constraints.len() * 2);
         let mut result = SolutionSet {
+            exact: true,
             constraints,
             variables: Vec::new(),
             nodes,
@@ -113,6 +116,9 @@ impl SolutionSet {
     }
 
     pub fn mark_mines(&self, board: &mut Board) {
+        if !self.exact {
+            return;
+        }
         let mut total_solutions = 0;
         for j in self.min..=self.max {
             total_solutions += self.solutions[j as usize];
@@ -129,7 +135,13 @@ impl SolutionSet {
         }
     }
 
-    pub fn enumerate_solutions(&mut self) {
+    /// Enumerates all the solutions of the set, unless it has more than `max_variables` variables.
+    /// In that case the set is [approximated](SolutionSet::approximate) instead.
+    pub fn enumerate_solutions(&mut self, max_variables: usize) {
+        if self.variables.len() > max_variables {
+            self.approximate();
+            return;
+        }
         for i in 0..self.solutions.len() {
             self.solutions[i] = 0;
             for j in 0..self.variables.len() {
@@ -168,7 +180,7 @@ impl SolutionSet {
             if variable_index[level as usize] < 0 {
                 let mut variable = None;
                 let mut i = 0;
-                while variable == None && i < self.constraints.len() {
+                while variable.is_none() && i < self.constraints.len() {
                     variable =
                         <RefCell<_>>::borrow(&self.constraints[i]).suggest_unassigned_variable();
                     i += 1;
@@ -234,6 +246,20 @@ impl SolutionSet {
         }
     }
 
+    /// Replaces the exact enumeration with bounds on the number of mines that don't require
+    /// looking at the single solutions: there are at least as many mines as the largest constant
+    /// and at most as many as the sum of the constants or the number of variables.
+    /// An approximated set never marks mines.
+    fn approximate(&mut self) {
+        self.exact = false;
+        let constants = self
+            .constraints
+            .iter()
+            .map(|constraint| <RefCell<_>>::borrow(constraint).constant);
+        self.max = self.min.min(self.variables.len() as isize);
+        self.min = constants.max().unwrap_or(0).min(self.max);
+    }
+
     pub fn get_variables(&self) -> Vec<Rc<RefCell<BoardCell>>> {
         self.variables.iter().map(Rc::clone).collect()
     }
@@ -277,12 +303,7 @@ impl PartialEq for ConstraintList {
 
 impl PartialOrd for ConstraintList {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(
-            self.constraints
-                .len()
-                .cmp(&other.constraints.len())
-                .reverse(),
-        )
+        Some(self.cmp(other))
     }
 }
 
@@ -290,7 +311,10 @@ impl Eq for ConstraintList {}
 
 impl Ord for ConstraintList {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(other).unwrap()
+        self.constraints
+            .len()
+            .cmp(&other.constraints.len())
+            .reverse()
     }
 }
 
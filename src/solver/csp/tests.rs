@@ -8,5 +8,21 @@ fn test() {
     let board = CSP_SOLVABLE[67];
     let ms: MSMatrix = board.into();
     let mut solver = CSPSolver::new(&ms);
-    solver.solve(ms.started_from());
+    solver.solve(ms.started_from().unwrap());
+}
+
+#[test]
+fn max_exact_variables() {
+    let (mut exact, mut approximated) = (0, 0);
+    for &board in &CSP_SOLVABLE[..50] {
+        let ms: MSMatrix = board.into();
+        if Solver::<MSMatrix>::solve(&mut CSPSolver::new(&ms), ms.started_from().unwrap()) {
+            exact += 1;
+        }
+        let mut solver = <CSPSolver as Solver<MSMatrix>>::new(&ms).with_max_exact_variables(0);
+        if Solver::<MSMatrix>::solve(&mut solver, ms.started_from().unwrap()) {
+            approximated += 1;
+        }
+    }
+    assert!(approximated < exact);
 }
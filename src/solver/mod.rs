@@ -1,9 +1,10 @@
-pub use csp::CSPSolver;
+pub use csp::{safe_cells, CSPSolver, DEFAULT_MAX_EXACT_VARIABLES};
 pub use single_point::SPSolver;
 
 use crate::{Coordinate, MineSweeper};
 
 mod csp;
+mod prefilter;
 mod single_point;
 
 #[cfg(test)]
@@ -14,6 +15,27 @@ mod tests;
 /// A solver can be any strategy applied to a game that tries to solve it (even randomly).
 pub trait Solver<M: MineSweeper> {
     fn new(ms: &M) -> Self;
+    /// Quickly checks if the strategy is surely going to fail on the given board,
+    /// so that generators can discard it without calling [`solve`](Solver::solve).
+    ///
+    /// # Default
+    /// The default implementation refuses the boards without [standard numbers](crate::MineSweeper::has_standard_numbers),
+    /// then looks for safe cells separated from the starting point by mines
+    /// that counting the remaining mines can't reveal,
+    /// and for pairs of cells that can't be told apart by any revealed number (50/50s).
+    /// Strategies that never fail should override this to return `false`.
+    fn is_hopeless(ms: &M, start_from: Coordinate) -> bool {
+        prefilter::is_hopeless(ms, start_from)
+    }
+    /// Returns `true` if the strategy can play boards whose numbers are computed
+    /// by a [`NumberRule`](crate::NumberRule) other than the standard one.
+    /// Generators return an error instead of looking forever for such a board the strategy accepts.
+    ///
+    /// # Default
+    /// The default implementation returns `false`.
+    fn supports_number_rules() -> bool {
+        false
+    }
     /// Apply the implemented strategy to a [`MineSweeper`](MineSweeper) game.
     /// Returns `true` if the board can be solved by the strategy, `false` otherwise.
     /// This method should be able to safely assume that the given coordinate is valid.
@@ -25,7 +47,7 @@ pub trait Solver<M: MineSweeper> {
     ///
     /// # Default
     /// The default implementation returns [`usize::MAX`](usize::MAX).
-    fn guessed() -> usize {
+    fn guessed(&self) -> usize {
         usize::MAX
     }
     /// Use this after a call to [`solve`](Solver::solve).
@@ -37,7 +59,7 @@ pub trait Solver<M: MineSweeper> {
     ///
     /// # Default
     /// The default implementation returns an empty vector.
-    fn get_unsolvable_clusters() -> Vec<Vec<Coordinate>> {
+    fn get_unsolvable_clusters(&self) -> Vec<Vec<Coordinate>> {
         Vec::new()
     }
 }
@@ -49,6 +71,14 @@ impl<M: MineSweeper> Solver<M> for NonDeterministic {
         Self {}
     }
 
+    fn is_hopeless(_: &M, _: Coordinate) -> bool {
+        false
+    }
+
+    fn supports_number_rules() -> bool {
+        true
+    }
+
     fn solve(&mut self, _: Coordinate) -> bool {
         true
     }
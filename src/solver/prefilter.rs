@@ -0,0 +1,76 @@
+use std::collections::VecDeque;
+
+use crate::{neighbors, CellContent, Coordinate, MineSweeper};
+
+/// Returns `true` if the board has a pattern that no strategy can solve without guessing,
+/// or if its numbers are not the standard ones.
+/// Runs in linear time, so it can be used to discard hopeless boards before running a solver.
+pub(crate) fn is_hopeless(ms: &impl MineSweeper, start_from: Coordinate) -> bool {
+    !ms.has_standard_numbers() || has_unreachable_cells(ms, start_from) || has_fifty_fifty(ms)
+}
+
+/// Returns `true` if some non-mine cells are separated from the starting point by a wall of mines
+/// and counting the remaining mines can't tell them apart from the mines behind the wall.
+///
+/// Cells can only be deduced to be safe if they are next to an open cell,
+/// so these cells can only be opened by counting the remaining mines at the end of the game.
+/// This works only if every mine is part of the wall, since nothing else is known about the cells behind it.
+pub(crate) fn has_unreachable_cells(ms: &impl MineSweeper, start_from: Coordinate) -> bool {
+    let (height, width) = (ms.height(), ms.width());
+    let mut reached = vec![vec![false; width]; height];
+    reached[start_from.0][start_from.1] = true;
+    let mut reachable = 1;
+    let mut wall = vec![vec![false; width]; height];
+    let mut wall_mines = 0;
+    let mut queue = VecDeque::from([start_from]);
+    while let Some(coord) = queue.pop_front() {
+        for neighbor @ (r, c) in neighbors(coord, height, width) {
+            if is_safe(ms, neighbor) {
+                if !reached[r][c] {
+                    reached[r][c] = true;
+                    reachable += 1;
+                    queue.push_back(neighbor);
+                }
+            } else if !wall[r][c] {
+                wall[r][c] = true;
+                wall_mines += 1;
+            }
+        }
+    }
+    reachable < height * width - ms.mines() && wall_mines < ms.mines()
+}
+
+/// Returns `true` if there is a mine next to a non-mine cell such that all the cells
+/// next to only one of them are mines.
+///
+/// In this case, swapping the two cells doesn't change any number that could ever be revealed,
+/// so the two cells can't be told apart.
+pub(crate) fn has_fifty_fifty(ms: &impl MineSweeper) -> bool {
+    let (height, width) = (ms.height(), ms.width());
+    (0..height)
+        .flat_map(|r| (0..width).map(move |c| (r, c)))
+        .filter(|&coord| !is_safe(ms, coord))
+        .any(|mine| {
+            neighbors(mine, height, width)
+                .filter(|&neighbor| is_safe(ms, neighbor))
+                .any(|safe| {
+                    let is_distinguishing = |coord: Coordinate, other: Coordinate| {
+                        coord != mine
+                            && coord != safe
+                            && !is_neighbor(coord, other)
+                            && is_safe(ms, coord)
+                    };
+                    !neighbors(mine, height, width).any(|coord| is_distinguishing(coord, safe))
+                        && !neighbors(safe, height, width)
+                            .any(|coord| is_distinguishing(coord, mine))
+                })
+        })
+}
+
+fn is_safe(ms: &impl MineSweeper, coord: Coordinate) -> bool {
+    ms.get_cell(coord).unwrap().content != CellContent::Mine
+}
+
+fn is_neighbor((r1, c1): Coordinate, (r2, c2): Coordinate) -> bool {
+    r1.abs_diff(r2) <= 1 && c1.abs_diff(c2) <= 1
+}
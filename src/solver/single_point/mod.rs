@@ -1,8 +1,8 @@
 use std::collections::VecDeque;
 
 use crate::{
-    count_neighboring_flags, get_neighboring_closed, iter_neighbors, CellContent, CellState,
-    Coordinate, MineSweeper, Solver,
+    count_neighboring_flags, get_neighboring_closed, neighbors, CellContent, CellState, Coordinate,
+    MineSweeper, MineSweeperExt, Neighbors, Solver,
 };
 
 #[cfg(test)]
@@ -19,7 +19,7 @@ impl<M: MineSweeper> SPSolver<M> {
         let mut cell;
         let mut opened;
         let mut neighboring_flags;
-        let mut neighboring_closed;
+        let mut neighboring_closed: Neighbors;
         let mut add_second_level_neighbors;
         while !queue.is_empty() {
             add_second_level_neighbors = false;
@@ -46,9 +46,7 @@ impl<M: MineSweeper> SPSolver<M> {
                         queue.extend(
                             neighboring_closed
                                 .iter()
-                                .flat_map(|&c| {
-                                    iter_neighbors(c, self.ms.height(), self.ms.width()).unwrap()
-                                })
+                                .flat_map(|&c| neighbors(c, self.ms.height(), self.ms.width()))
                                 .filter(|&c| self.ms.get_cell(c).unwrap().state == CellState::Open),
                         );
                     }
@@ -59,7 +57,7 @@ impl<M: MineSweeper> SPSolver<M> {
                 ),
             }
         }
-        self.ms.get_game_state().opened == self.ms.width() * self.ms.height() - self.ms.mines()
+        self.ms.is_cleared()
     }
 
     fn unknowns_near(ms: &impl MineSweeper, (r, c): Coordinate) {
@@ -85,6 +83,11 @@ impl<M: MineSweeper + Clone> Solver<M> for SPSolver<M> {
     }
 
     fn solve(&mut self, start_from: Coordinate) -> bool {
-        self.apply(start_from)
+        self.ms.has_standard_numbers() && self.apply(start_from)
+    }
+
+    /// The solver never guesses: it gives up instead.
+    fn guessed(&self) -> usize {
+        0
     }
 }
@@ -0,0 +1,151 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{Difficulty, Move, SavedGame};
+
+/// A place where [saved games](SavedGame) are kept, identified by a game id.
+///
+/// Servers can use this to move the idle games out of memory and restore them when needed.
+pub trait Storage {
+    type Error;
+
+    /// Saves a game, replacing the one with the same id if any.
+    fn save(&mut self, id: &str, game: &SavedGame) -> Result<(), Self::Error>;
+    /// Loads a game, or returns `None` if there is no game with the given id.
+    fn load(&self, id: &str) -> Result<Option<SavedGame>, Self::Error>;
+    /// Deletes a game, if it exists.
+    fn delete(&mut self, id: &str) -> Result<(), Self::Error>;
+    /// Returns the ids of all the saved games, in no particular order.
+    fn list(&self) -> Result<Vec<String>, Self::Error>;
+}
+
+/// A [`Storage`] keeping each game in a text file in the given directory.
+///
+/// Ids can only contain ASCII letters, digits, `-` and `_`,
+/// so that they are valid file names on every platform.
+#[derive(Debug, Clone)]
+pub struct FileStorage {
+    dir: PathBuf,
+}
+
+const EXTENSION: &str = "msave";
+
+impl FileStorage {
+    /// Uses the given directory, creating it if it doesn't exist.
+    pub fn new(dir: impl AsRef<Path>) -> io::Result<Self> {
+        fs::create_dir_all(&dir)?;
+        Ok(FileStorage {
+            dir: dir.as_ref().to_path_buf(),
+        })
+    }
+
+    fn path(&self, id: &str) -> io::Result<PathBuf> {
+        if id.is_empty()
+            || !id
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid game id {id:?}"),
+            ));
+        }
+        Ok(self.dir.join(id).with_extension(EXTENSION))
+    }
+}
+
+impl Storage for FileStorage {
+    type Error = io::Error;
+
+    fn save(&mut self, id: &str, game: &SavedGame) -> io::Result<()> {
+        // writing to another file first, so that a crash never leaves a partial save
+        let path = self.path(id)?;
+        let temp = path.with_extension("tmp");
+        fs::write(&temp, encode(game))?;
+        fs::rename(temp, path)
+    }
+
+    fn load(&self, id: &str) -> io::Result<Option<SavedGame>> {
+        match fs::read_to_string(self.path(id)?) {
+            Ok(text) => decode(&text).map(Some).ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("corrupted game {id:?}"))
+            }),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    fn delete(&mut self, id: &str) -> io::Result<()> {
+        match fs::remove_file(self.path(id)?) {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error),
+            _ => Ok(()),
+        }
+    }
+
+    fn list(&self) -> io::Result<Vec<String>> {
+        let mut ids = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == EXTENSION)
+            {
+                if let Some(id) = path.file_stem().and_then(|stem| stem.to_str()) {
+                    ids.push(id.to_string());
+                }
+            }
+        }
+        Ok(ids)
+    }
+}
+
+/// Writes the difficulty and the seed (`-` if redacted) on the first line,
+/// then a line for each move: `o` or `f` followed by the coordinate.
+fn encode(game: &SavedGame) -> String {
+    let (height, width, mines) = game.difficulty.into();
+    let seed = game.seed.map_or("-".to_string(), |seed| seed.to_string());
+    let mut text = format!("{height} {width} {mines} {seed}\n");
+    for action in &game.moves {
+        let (kind, (r, c)) = match *action {
+            Move::Open(coord) => ('o', coord),
+            Move::ToggleFlag(coord) => ('f', coord),
+        };
+        text += &format!("{kind} {r} {c}\n");
+    }
+    text
+}
+
+fn decode(text: &str) -> Option<SavedGame> {
+    let mut lines = text.lines();
+    let header: Vec<_> = lines.next()?.split(' ').collect();
+    let [height, width, mines, seed] = header[..] else {
+        return None;
+    };
+    let mut game = SavedGame {
+        difficulty: Difficulty::custom(
+            height.parse().ok()?,
+            width.parse().ok()?,
+            mines.parse().ok()?,
+        ),
+        seed: match seed {
+            "-" => None,
+            seed => Some(seed.parse().ok()?),
+        },
+        moves: Vec::new(),
+    };
+    for line in lines {
+        let words: Vec<_> = line.split(' ').collect();
+        let [kind, r, c] = words[..] else {
+            return None;
+        };
+        let coord = (r.parse().ok()?, c.parse().ok()?);
+        game.moves.push(match kind {
+            "o" => Move::Open(coord),
+            "f" => Move::ToggleFlag(coord),
+            _ => return None,
+        });
+    }
+    Some(game)
+}
@@ -0,0 +1,103 @@
+//! Board observations as [`ndarray`](::ndarray) tensors and moves as action indices,
+//! the usual inputs and outputs of machine learning models.
+//!
+//! ```
+//! use mine_sweeperr::{env::Environment, tensor, Difficulty, MSMatrix};
+//!
+//! let mut env = Environment::<MSMatrix>::new(Difficulty::easy(), 42);
+//! env.reset();
+//! assert_eq!(env.observation_tensor().shape(), &[tensor::CHANNELS, 9, 9]);
+//! let (observation, _, done) = env.step_index(4 * 9 + 4);
+//! assert!(observation.slice(ndarray::s![tensor::CLOSED, .., ..]).sum() < 81.0);
+//! assert!(!done);
+//! ```
+
+use ndarray::Array3;
+
+use crate::{
+    env::{Done, Environment, Reward},
+    BoardView, CellView, MineSweeper, Move,
+};
+
+/// The number of channels of an observation:
+/// one for each number from `0` to `8`, followed by [`CLOSED`] and [`FLAGGED`].
+pub const CHANNELS: usize = 11;
+
+/// The channel with `1` on closed cells, including flagged ones.
+pub const CLOSED: usize = 9;
+
+/// The channel with `1` on flagged cells.
+pub const FLAGGED: usize = 10;
+
+/// Converts a view into a tensor with shape `(CHANNELS, height, width)`.
+///
+/// For each cell, channel `n` is `1` if the cell is open and shows the number `n`.
+/// Opened mines have all the channels set to `0`.
+pub fn observation(view: &BoardView) -> Array3<f32> {
+    let mut tensor = Array3::zeros((CHANNELS, view.height, view.width));
+    for (i, cell) in view.cells.iter().enumerate() {
+        let (r, c) = (i / view.width, i % view.width);
+        match *cell {
+            CellView::Closed => tensor[(CLOSED, r, c)] = 1.0,
+            CellView::Flagged => {
+                tensor[(CLOSED, r, c)] = 1.0;
+                tensor[(FLAGGED, r, c)] = 1.0;
+            }
+            CellView::Number(n) => tensor[(n as usize, r, c)] = 1.0,
+            CellView::Mine => {}
+        }
+    }
+    tensor
+}
+
+/// Returns the number of actions on a board with the given size:
+/// opening each cell, followed by flagging each cell, in row-major order.
+pub fn action_count(height: usize, width: usize) -> usize {
+    2 * height * width
+}
+
+/// Returns the move with the given index, or `None` if the index is too big.
+pub fn action(index: usize, height: usize, width: usize) -> Option<Move> {
+    let cells = height * width;
+    let coord = |i: usize| (i / width, i % width);
+    if index < cells {
+        Some(Move::Open(coord(index)))
+    } else if index < 2 * cells {
+        Some(Move::ToggleFlag(coord(index - cells)))
+    } else {
+        None
+    }
+}
+
+/// Returns the index of a move on a board with the given size, the inverse of [`action`].
+pub fn action_index(action: Move, height: usize, width: usize) -> usize {
+    match action {
+        Move::Open((r, c)) => r * width + c,
+        Move::ToggleFlag((r, c)) => height * width + r * width + c,
+    }
+}
+
+impl<M: MineSweeper> Environment<M> {
+    /// Returns what the agent currently sees, as a [tensor](observation).
+    pub fn observation_tensor(&self) -> Array3<f32> {
+        observation(&self.observation())
+    }
+
+    /// Plays the move with the given [index](action), as [`step`](Environment::step).
+    ///
+    /// Indexes that don't correspond to a move get the [invalid](crate::env::Rewards::invalid) reward.
+    pub fn step_index(&mut self, index: usize) -> (Array3<f32>, Reward, Done) {
+        let board = self.board();
+        match action(index, board.height(), board.width()) {
+            Some(action) => {
+                let (view, reward, done) = self.step(action);
+                (observation(&view), reward, done)
+            }
+            None => (
+                self.observation_tensor(),
+                self.rewards().invalid,
+                self.is_done(),
+            ),
+        }
+    }
+}
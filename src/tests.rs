@@ -191,7 +191,11 @@ CCCCCCCCCCCC
 }
 
 mod test_types {
-    use crate::Difficulty;
+    use crate::{
+        board, validate_difficulty, validate_setup, Cell, CellContent, CellState, Difficulty,
+        Error, ErrorKind, MSConst, MSHash, MSMatrix, MSSparse, MineSweeper, MineSweeperExt, Pcg32,
+        ValidSetup,
+    };
 
     #[test]
     fn difficulty() {
@@ -209,12 +213,182 @@ mod test_types {
         difficulty = (10, 10, 0.5).into();
         assert_eq!(difficulty, Difficulty::custom(10, 10, 50));
     }
+
+    #[test]
+    fn setup() {
+        let setup = validate_setup((4, 5, 10).into(), (3, 4)).unwrap();
+        assert_eq!(setup.difficulty(), (4, 5, 10).into());
+        assert_eq!(setup.start_from(), (3, 4));
+        assert_eq!(
+            validate_setup((0, 5, 0).into(), (0, 0)),
+            Err(Error::InvalidParameters)
+        );
+        // the arithmetic on huge boards doesn't overflow
+        assert_eq!(
+            validate_difficulty(Difficulty::custom(usize::MAX, 2, 0)),
+            Err(Error::InvalidParameters)
+        );
+        assert_eq!(
+            validate_difficulty(Difficulty::custom(3, 4, usize::MAX)),
+            Err(Error::TooManyMines {
+                mines: usize::MAX,
+                max: 2
+            })
+        );
+        assert_eq!(
+            validate_setup((4, 5, 11).into(), (0, 0)),
+            Err(Error::TooManyMines { mines: 11, max: 10 })
+        );
+        assert_eq!(
+            validate_setup((4, 5, 10).into(), (4, 0)),
+            Err(Error::OutOfBounds {
+                coord: (4, 0),
+                height: 4,
+                width: 5
+            })
+        );
+
+        fn from_setup<M: MineSweeper>(setup: ValidSetup) {
+            let ms = M::from_setup(setup, &mut Pcg32::new(0)).unwrap();
+            assert_eq!(ms.started_from(), Some(setup.start_from()));
+            assert_eq!(ms.mines(), setup.difficulty().mines());
+            assert_eq!(
+                ms.get_cell(setup.start_from()).unwrap().content,
+                CellContent::Number(0)
+            );
+        }
+        from_setup::<MSMatrix>(setup);
+        from_setup::<MSSparse>(setup);
+        from_setup::<MSHash>(setup);
+        from_setup::<MSConst<4, 5>>(setup);
+        assert_eq!(
+            MSConst::<9, 9>::from_setup(setup, &mut Pcg32::new(0)).err(),
+            Some(Error::SizeMismatch {
+                height: 4,
+                width: 5,
+                capacity: 81
+            })
+        );
+    }
+
+    #[test]
+    fn cell_to_byte() {
+        assert_eq!(Cell::closed().to_byte(), 0);
+        assert_eq!(
+            Cell::new(CellState::Open, CellContent::Number(8)).to_byte(),
+            0x18
+        );
+        assert_eq!(
+            Cell::new(CellState::Open, CellContent::Mine).to_byte(),
+            0x19
+        );
+        assert_eq!(
+            Cell::new(CellState::Flagged, CellContent::Number(3)).to_byte(),
+            0x23
+        );
+    }
+
+    #[test]
+    fn error() {
+        let error: Box<dyn std::error::Error> = Box::new(Error::AlreadyOpen);
+        assert_eq!(error.to_string(), "the cell is already open");
+
+        let error = Error::OutOfBounds {
+            coord: (9, 0),
+            height: 9,
+            width: 9,
+        };
+        assert_eq!(
+            error.to_string(),
+            "the coordinate (9, 0) is out of the bounds of a 9x9 board"
+        );
+        assert_eq!(error.kind(), ErrorKind::Coordinate);
+        assert_eq!(Error::InvalidParameters.kind(), ErrorKind::Setup);
+        assert_eq!(Error::UnsupportedNumberRule.kind(), ErrorKind::Setup);
+        assert_eq!(Error::NotStarted.kind(), ErrorKind::Move);
+        assert_eq!(
+            Error::NotStarted.to_string(),
+            "the mines have not been placed yet"
+        );
+        assert_eq!(Error::CellsAlreadyOpen.kind(), ErrorKind::Move);
+        let error = Error::SizeMismatch {
+            height: 9,
+            width: 9,
+            capacity: 80,
+        };
+        assert_eq!(
+            error.to_string(),
+            "a 9x9 board doesn't fit in a storage made for 80 cells"
+        );
+        assert_eq!(error.kind(), ErrorKind::Setup);
+    }
+
+    #[test]
+    fn ext() {
+        let mut ms = board![
+            "* 1 ."
+            "1 1 ."
+            ". . ."
+        ];
+        ms.toggle_flag((0, 0)).unwrap();
+        ms.open_one((0, 1)).unwrap();
+        assert!(ms.is_mine((0, 0)).unwrap());
+        assert!(!ms.is_mine((0, 1)).unwrap());
+        assert!(ms.is_flagged((0, 0)).unwrap());
+        assert!(!ms.is_open((0, 0)).unwrap());
+        assert!(ms.is_open((0, 1)).unwrap());
+        assert!(!ms.is_flagged((0, 1)).unwrap());
+        assert!(ms.is_open((3, 0)).is_err());
+        assert_eq!(ms.remaining_mines(), 0);
+        assert_eq!(ms.closed_cells(), 7);
+        assert_eq!(ms.safe_cells_remaining(), 7);
+        assert!(!ms.is_cleared());
+
+        // An exploded mine doesn't count as a safe cell.
+        ms.toggle_flag((0, 0)).unwrap();
+        ms.open_one((0, 0)).unwrap();
+        assert_eq!(ms.safe_cells_remaining(), 7);
+        // Wrong flags and exploded mines can outnumber the mines.
+        ms.toggle_flag((2, 2)).unwrap();
+        assert_eq!(ms.remaining_mines(), 0);
+        assert_eq!(ms.safe_cells_remaining(), 7);
+        ms.toggle_flag((2, 2)).unwrap();
+        ms.open((2, 2)).unwrap();
+        assert_eq!(ms.safe_cells_remaining(), 0);
+        assert!(ms.is_cleared());
+    }
+
+    #[test]
+    fn iter_frontier() {
+        let mut ms = board![
+            "* 1 ."
+            "1 1 ."
+            ". . ."
+        ];
+        assert_eq!(ms.iter_frontier().count(), 0);
+        ms.toggle_flag((0, 0)).unwrap();
+        ms.open_one((0, 1)).unwrap();
+        let frontier: Vec<_> = ms.iter_frontier().map(|(coord, _)| coord).collect();
+        assert_eq!(frontier, vec![(0, 2), (1, 0), (1, 1), (1, 2)]);
+        ms.open_one((1, 1)).unwrap();
+        let frontier: Vec<_> = ms
+            .iter_frontier()
+            .map(|(coord, numbers)| (coord, numbers.collect::<Vec<_>>()))
+            .collect();
+        assert_eq!(frontier[0], ((0, 2), vec![((0, 1), 1), ((1, 1), 1)]));
+        assert_eq!(frontier.len(), 6);
+        assert_eq!(frontier[5], ((2, 2), vec![((1, 1), 1)]));
+    }
 }
 
 mod utils {
     use std::collections::HashSet;
 
-    use crate::{get_column_numbers, iter_neighbors};
+    use crate::{
+        count_neighboring_closed, count_neighboring_flags, count_neighboring_mines, flood_open,
+        get_column_numbers, get_neighboring_closed, get_neighboring_flags, get_neighboring_mines,
+        is_in_safe_zone, iter_neighbors, CellState, Error, MSMatrix, MineSweeper,
+    };
 
     #[test]
     fn neighbors() {
@@ -260,6 +434,119 @@ mod utils {
                 (0, 1)
             ])
         );
+
+        for coord in [
+            (h, 0),
+            (0, w),
+            (h, w),
+            (h + 5, w + 5),
+            (usize::MAX, 0),
+            (0, usize::MAX),
+            (usize::MAX, usize::MAX),
+        ] {
+            assert!(iter_neighbors(coord, h, w).is_err());
+            assert_eq!(crate::neighbors(coord, h, w).count(), 0);
+        }
+        for coord in [(0, 0), (h - 1, w - 2), (1, 1)] {
+            assert!(crate::neighbors(coord, h, w).eq(iter_neighbors(coord, h, w).unwrap()));
+        }
+    }
+
+    #[test]
+    fn flood_open_chord() {
+        fn open_both(ms: &mut MSMatrix, coord: (usize, usize)) -> (usize, usize) {
+            let mut expected = ms.clone();
+            let expected_result = expected.open(coord).unwrap();
+            let result = flood_open(ms, coord, |ms, coord| {
+                ms.open_one(coord).unwrap();
+            })
+            .unwrap();
+            assert_eq!(result, expected_result);
+            assert_eq!(*ms, expected);
+            (result.cells_opened, result.mines_exploded)
+        }
+
+        let mut ms = MSMatrix::from((4, 4, &[0, 15][..], (2, 0)));
+        assert_eq!(open_both(&mut ms, (0, 1)), (1, 0));
+        // Chording a number with enough flags opens all the other neighbors.
+        ms.toggle_flag((0, 0)).unwrap();
+        assert_eq!(open_both(&mut ms, (0, 1)), (13, 0));
+        assert_eq!(ms.get_cell((0, 0)).unwrap().state, CellState::Flagged);
+        assert_eq!(ms.get_cell((3, 3)).unwrap().state, CellState::Closed);
+
+        // A wrong flag makes the chord explode the mine.
+        let mut ms = MSMatrix::from((4, 4, &[0, 15][..], (2, 0)));
+        assert_eq!(open_both(&mut ms, (0, 1)), (1, 0));
+        ms.toggle_flag((1, 0)).unwrap();
+        assert_eq!(open_both(&mut ms, (0, 1)), (13, 1));
+
+        assert!(matches!(
+            flood_open(&mut ms, (4, 0), |_, _| unreachable!()),
+            Err(Error::OutOfBounds { .. })
+        ));
+    }
+
+    #[test]
+    fn flood_open_large_area() {
+        // Cells queued more than once used to be expanded each time,
+        // making the queue grow exponentially on large empty areas.
+        let mut ms = MSMatrix::from((64, 64, &[0][..], (63, 63)));
+        let mut opened = 0;
+        let result = flood_open(&mut ms, (63, 63), |ms, coord| {
+            opened += 1;
+            ms.open_one(coord).unwrap();
+        })
+        .unwrap();
+        assert_eq!((result.cells_opened, opened), (64 * 64 - 1, 64 * 64 - 1));
+    }
+
+    #[test]
+    fn open_large_area() {
+        // MSMatrix::open had the same problem as flood_open.
+        let mut ms = MSMatrix::from((64, 64, &[0][..], (63, 63)));
+        assert_eq!(ms.open((63, 63)).unwrap().cells_opened, 64 * 64 - 1);
+    }
+
+    #[test]
+    fn neighboring_helpers() {
+        let mut ms = MSMatrix::from((4, 4, &[0, 5, 15][..], (3, 0)));
+        ms.toggle_flag((0, 0)).unwrap();
+        ms.toggle_flag((0, 2)).unwrap();
+        ms.open_one((1, 0)).unwrap();
+
+        let flags: HashSet<_> = get_neighboring_flags(&ms, (0, 1)).collect();
+        assert_eq!(flags, HashSet::from([(0, 0), (0, 2)]));
+        assert_eq!(count_neighboring_flags(&ms, (0, 1)), 2);
+
+        let closed: HashSet<_> = get_neighboring_closed(&ms, (0, 1)).into_iter().collect();
+        assert_eq!(closed, HashSet::from([(1, 1), (1, 2)]));
+        assert_eq!(count_neighboring_closed(&ms, (0, 1)), 2);
+
+        let mines: HashSet<_> = get_neighboring_mines(&ms, (0, 1)).collect();
+        assert_eq!(mines, HashSet::from([(0, 0), (1, 1)]));
+        assert_eq!(count_neighboring_mines(&ms, (0, 1)), 2);
+        assert_eq!(count_neighboring_mines(&ms, (2, 2)), 2);
+
+        assert_eq!(count_neighboring_flags(&ms, (4, 4)), 0);
+        assert_eq!(count_neighboring_closed(&ms, (4, 4)), 0);
+        assert_eq!(count_neighboring_mines(&ms, (4, 4)), 0);
+    }
+
+    #[test]
+    fn safe_zone() {
+        let (h, w) = (10, 10);
+        for start_from in [(0, 0), (5, 5), (h - 1, w - 1), (0, w - 1)] {
+            let mut expected: HashSet<_> = iter_neighbors(start_from, h, w).unwrap().collect();
+            expected.insert(start_from);
+            for i in 0..h {
+                for j in 0..w {
+                    assert_eq!(
+                        is_in_safe_zone((i, j), start_from),
+                        expected.contains(&(i, j))
+                    );
+                }
+            }
+        }
     }
 
     #[test]
@@ -303,3 +590,1023 @@ mod utils {
         assert_eq!(expected, get_column_numbers(11, 105, true));
     }
 }
+
+mod game {
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use crate::{
+        board, AutosavePolicy, CellState, CellView, Difficulty, Error, Game, GameRules, MSMatrix,
+        MineSweeper, MineSweeperExt, Move, SavedGame, StartPolicy, Storage,
+    };
+
+    #[derive(Default, Clone)]
+    struct Memory(Arc<Mutex<HashMap<String, SavedGame>>>);
+
+    impl Storage for Memory {
+        type Error = ();
+
+        fn save(&mut self, id: &str, game: &SavedGame) -> Result<(), ()> {
+            self.0.lock().unwrap().insert(id.to_string(), game.clone());
+            Ok(())
+        }
+
+        fn load(&self, id: &str) -> Result<Option<SavedGame>, ()> {
+            Ok(self.0.lock().unwrap().get(id).cloned())
+        }
+
+        fn delete(&mut self, id: &str) -> Result<(), ()> {
+            self.0.lock().unwrap().remove(id);
+            Ok(())
+        }
+
+        fn list(&self) -> Result<Vec<String>, ()> {
+            Ok(self.0.lock().unwrap().keys().cloned().collect())
+        }
+    }
+
+    #[test]
+    fn moves() {
+        let mut game = Game::new(board!["*..", "...", "..."]);
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let hook_log = Arc::clone(&log);
+        game.on_move(move |_, action, moves| hook_log.lock().unwrap().push((action, moves)));
+
+        game.toggle_flag((0, 0)).unwrap();
+        assert_eq!(
+            game.toggle_flag((3, 0)),
+            Err(Error::OutOfBounds {
+                coord: (3, 0),
+                height: 3,
+                width: 3
+            })
+        );
+        game.play(Move::Open((2, 2))).unwrap();
+        assert_eq!(game.play(Move::ToggleFlag((2, 2))), Err(Error::AlreadyOpen));
+        assert_eq!(game.moves(), 2);
+        assert_eq!(
+            *log.lock().unwrap(),
+            [(Move::ToggleFlag((0, 0)), 1), (Move::Open((2, 2)), 2)]
+        );
+        assert_eq!(game.board().get_game_state().opened, 8);
+    }
+
+    #[test]
+    fn autosave() {
+        let difficulty = Difficulty::medium();
+        let board = <MSMatrix as MineSweeper>::from_seed(difficulty, (8, 8), 3).unwrap();
+        let mut game = Game::new(board);
+        let storage = Memory::default();
+        let save = SavedGame::new(difficulty, 3);
+        game.autosave(
+            storage.clone(),
+            "a",
+            save.clone(),
+            AutosavePolicy::EveryMoves(2),
+        );
+        game.autosave(
+            storage.clone(),
+            "b",
+            save,
+            AutosavePolicy::Every(Duration::ZERO),
+        );
+
+        game.open((8, 8)).unwrap();
+        assert_eq!(storage.load("a").unwrap(), None);
+        assert_eq!(storage.load("b").unwrap().unwrap().moves.len(), 1);
+        game.toggle_flag((0, 0)).unwrap();
+        game.toggle_flag((0, 1)).unwrap();
+        let saved = storage.load("a").unwrap().unwrap();
+        assert_eq!(saved.moves.len(), 2);
+        assert_eq!(storage.load("b").unwrap().unwrap().moves.len(), 3);
+
+        let mut restored: MSMatrix = saved.restore().unwrap();
+        restored.toggle_flag((0, 1)).unwrap();
+        assert_eq!(&restored, game.board());
+    }
+
+    #[test]
+    fn rules() {
+        let rules = GameRules {
+            chord: false,
+            flag_limit: true,
+            lives: 2,
+            start: StartPolicy::Free,
+            question_marks: false,
+            auto_finish: true,
+        };
+        let mut game = Game::with_rules(board!["*.*", "..."], rules);
+        assert_eq!(game.rules(), rules);
+        game.open((1, 1)).unwrap();
+        assert_eq!(game.open((1, 1)), Err(Error::AlreadyOpen));
+        game.toggle_flag((1, 0)).unwrap();
+        game.toggle_flag((0, 1)).unwrap();
+        assert_eq!(game.toggle_flag((0, 0)), Err(Error::TooManyFlags));
+        game.toggle_flag((0, 1)).unwrap();
+
+        game.open((0, 0)).unwrap();
+        assert_eq!((game.lives_left(), game.is_lost()), (1, false));
+        game.toggle_flag((1, 0)).unwrap();
+        game.open((1, 0)).unwrap();
+        game.open((0, 1)).unwrap();
+        game.open((1, 2)).unwrap();
+        // the last mine is flagged automatically
+        assert_eq!(
+            game.board().get_cell((0, 2)).unwrap().state,
+            CellState::Flagged
+        );
+        // and flagging it counts as a move
+        assert_eq!(game.moves(), 10);
+
+        let mut game = Game::new(board!["*..", "..."]);
+        game.open((0, 0)).unwrap();
+        assert!(game.is_lost());
+        assert_eq!(game.open((1, 2)), Err(Error::GameOver));
+        assert_eq!(game.toggle_flag((1, 2)), Err(Error::GameOver));
+
+        // a game without lives would be lost before the first move
+        let rules = GameRules {
+            lives: 0,
+            ..GameRules::default()
+        };
+        let mut game = Game::with_rules(board!["*..", "..."], rules);
+        assert_eq!(game.rules().lives, 1);
+        assert!(!game.is_lost());
+        game.open((1, 2)).unwrap();
+        let game = Game::builder(board!["*..", "..."]).lives(0).build();
+        assert_eq!(game.lives_left(), 1);
+    }
+
+    #[test]
+    fn start_policy() {
+        let rules = GameRules {
+            start: StartPolicy::OpenStart,
+            ..GameRules::default()
+        };
+        let game = Game::with_rules(board![start: (1, 2); "*..", "..."], rules);
+        assert_eq!(game.board().get_game_state().opened, 4);
+        assert_eq!(game.moves(), 1);
+
+        // boards whose mines are not placed start from the center
+        let board = <MSMatrix as MineSweeper>::new_unstarted(Difficulty::easy()).unwrap();
+        let game = Game::with_rules(board, rules);
+        assert_eq!(game.board().started_from(), Some((4, 4)));
+        assert!(game.board().get_game_state().opened > 0);
+
+        // the first open is saved and the mines are placed with the seed, so the game can be restored
+        let storage = Memory::default();
+        let board = <MSMatrix as MineSweeper>::new_unstarted(Difficulty::easy()).unwrap();
+        let mut game = Game::builder(board)
+            .rules(rules)
+            .seed(7)
+            .autosave(
+                storage.clone(),
+                "start",
+                SavedGame::new(Difficulty::easy(), 7),
+                AutosavePolicy::EveryMoves(1),
+            )
+            .build();
+        let saved = storage.load("start").unwrap().unwrap();
+        assert_eq!(saved.moves, vec![Move::Open((4, 4))]);
+        assert_eq!(&saved.restore::<MSMatrix>().unwrap(), game.board());
+        game.toggle_flag((0, 0)).unwrap();
+        let saved = storage.load("start").unwrap().unwrap();
+        assert_eq!(&saved.restore::<MSMatrix>().unwrap(), game.board());
+
+        let game = Game::new(board![start: (1, 2); "*..", "..."]);
+        assert_eq!(game.board().get_game_state().opened, 0);
+    }
+
+    #[test]
+    fn question_marks() {
+        let rules = GameRules {
+            question_marks: true,
+            flag_limit: true,
+            ..GameRules::default()
+        };
+        let mut game = Game::with_rules(board!["*..", "..."], rules);
+        assert_eq!(game.toggle_flag((0, 1)), Ok(CellState::Flagged));
+        assert_eq!(game.toggle_flag((0, 1)), Ok(CellState::Closed));
+        assert!(game.is_question_marked((0, 1)));
+        // removing the mark doesn't change the board nor count as a move
+        assert_eq!(game.toggle_flag((0, 1)), Ok(CellState::Closed));
+        assert!(!game.is_question_marked((0, 1)));
+        assert_eq!(game.moves(), 2);
+
+        // opening a marked cell removes the mark, also when it is opened by flooding
+        game.toggle_flag((1, 2)).unwrap();
+        game.toggle_flag((1, 2)).unwrap();
+        game.toggle_flag((0, 2)).unwrap();
+        game.toggle_flag((0, 2)).unwrap();
+        game.open((1, 2)).unwrap();
+        assert!(!game.is_question_marked((1, 2)));
+        assert!(!game.is_question_marked((0, 2)));
+
+        let mut game = Game::new(board!["*..", "..."]);
+        game.toggle_flag((0, 1)).unwrap();
+        game.toggle_flag((0, 1)).unwrap();
+        assert!(!game.is_question_marked((0, 1)));
+        assert_eq!(game.toggle_flag((0, 1)), Ok(CellState::Flagged));
+    }
+
+    #[test]
+    fn builder() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let hook_seen = Arc::clone(&seen);
+        let mut game = Game::builder(board![start: (1, 2); "*..", "..."])
+            .chord(false)
+            .lives(2)
+            .auto_finish(true)
+            .start(StartPolicy::OpenStart)
+            .on_move(move |_, action, _| hook_seen.lock().unwrap().push(action))
+            .build();
+        assert_eq!(
+            game.rules(),
+            GameRules {
+                chord: false,
+                lives: 2,
+                start: StartPolicy::OpenStart,
+                auto_finish: true,
+                ..GameRules::default()
+            }
+        );
+        assert_eq!(game.board().get_game_state().opened, 4);
+        game.open((1, 0)).unwrap();
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![
+                Move::Open((1, 2)),
+                Move::Open((1, 0)),
+                Move::ToggleFlag((0, 0))
+            ]
+        );
+        assert!(game.board().is_cleared());
+
+        let rules = GameRules {
+            flag_limit: true,
+            ..GameRules::default()
+        };
+        let game = Game::builder(board!["*..", "..."]).rules(rules).build();
+        assert_eq!(game.rules(), rules);
+    }
+
+    #[test]
+    fn watch() {
+        let mut game = Game::new(board!["*..", "..."]);
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let hook_seen = Arc::clone(&seen);
+        game.watch(move |view, _, _| hook_seen.lock().unwrap().push(view.board_view().cells));
+
+        game.open((0, 1)).unwrap();
+        assert_eq!(
+            seen.lock().unwrap()[0][..2],
+            [CellView::Closed, CellView::Number(1)]
+        );
+        let view = game.spectator();
+        assert_eq!(view.get_cell((0, 0)), Ok(CellView::Closed));
+        assert_eq!((view.height(), view.width(), view.mines()), (2, 3, 1));
+        assert_eq!(view.position_hash(), game.board().position_hash());
+    }
+}
+
+mod random {
+    use crate::{Difficulty, MSHash, MSMatrix, MineSweeper, Pcg32, Random};
+
+    #[test]
+    fn pcg32() {
+        let (mut a, mut b) = (Pcg32::new(42), Pcg32::new(42));
+        let sequence: Vec<_> = (0..10).map(|_| a.next_u64()).collect();
+        assert!((0..10).map(|_| b.next_u64()).eq(sequence.iter().copied()));
+        assert_ne!(
+            sequence,
+            (0..10)
+                .map(|_| Pcg32::new(43).next_u64())
+                .collect::<Vec<_>>()
+        );
+
+        let mut counts = [0; 6];
+        for _ in 0..6000 {
+            counts[a.below(6)] += 1;
+        }
+        assert!(counts.iter().all(|&count| (800..1200).contains(&count)));
+    }
+
+    #[test]
+    fn from_seed() {
+        let difficulty = Difficulty::medium();
+        let ms = <MSMatrix as MineSweeper>::from_seed(difficulty, (0, 0), 7).unwrap();
+        assert_eq!(
+            ms,
+            <MSMatrix as MineSweeper>::from_seed(difficulty, (0, 0), 7).unwrap()
+        );
+        assert_ne!(
+            ms,
+            <MSMatrix as MineSweeper>::from_seed(difficulty, (0, 0), 8).unwrap()
+        );
+        assert_eq!(
+            ms.cells_snapshot(),
+            MSHash::from_seed(difficulty, (0, 0), 7)
+                .unwrap()
+                .cells_snapshot()
+        );
+    }
+}
+
+mod frontend {
+    use crate::{
+        board,
+        frontend::{BoardRenderer, InputTranslator},
+        Cell, CellState, Coordinate, MineSweeper,
+    };
+
+    struct Screen(Vec<(Coordinate, char)>);
+
+    impl BoardRenderer for Screen {
+        type Sprite = char;
+
+        fn sprite(&self, cell: Cell) -> char {
+            match cell.state {
+                CellState::Open => 'O',
+                _ => 'C',
+            }
+        }
+
+        fn draw(&mut self, coord: Coordinate, sprite: char) {
+            self.0.push((coord, sprite));
+        }
+    }
+
+    impl InputTranslator for Screen {
+        fn cell_size(&self) -> (f32, f32) {
+            (16.0, 10.0)
+        }
+
+        fn origin(&self) -> (f32, f32) {
+            (8.0, 4.0)
+        }
+    }
+
+    #[test]
+    fn render_and_translate() {
+        let mut ms = board!["*..", "..."];
+        ms.open((0, 1)).unwrap();
+        let mut screen = Screen(Vec::new());
+        screen.render(&ms);
+        assert_eq!(screen.0.len(), 6);
+        assert_eq!(screen.0[1], ((0, 1), 'O'));
+        assert_eq!(screen.0[5], ((1, 2), 'C'));
+
+        assert_eq!(screen.to_coordinate(&ms, (8.0, 4.0)), Some((0, 0)));
+        assert_eq!(screen.to_coordinate(&ms, (55.9, 23.9)), Some((1, 2)));
+        assert_eq!(screen.to_coordinate(&ms, (56.0, 4.0)), None);
+        assert_eq!(screen.to_coordinate(&ms, (7.0, 4.0)), None);
+    }
+}
+
+mod view {
+    use crate::{board, BoardView, CellView, MineSweeper, SpectatorView};
+
+    #[test]
+    fn board_view() {
+        let mut ms = board!["*..", "..*"];
+        ms.open((0, 1)).unwrap();
+        ms.open((1, 2)).unwrap();
+        ms.toggle_flag((0, 0)).unwrap();
+        let view = BoardView::new(&ms);
+        assert_eq!((view.height, view.width), (2, 3));
+        assert_eq!((view.flagged, view.opened, view.mines_left), (1, 2, 0));
+        assert_eq!(
+            view.cells,
+            [
+                CellView::Flagged,
+                CellView::Number(2),
+                CellView::Closed,
+                CellView::Closed,
+                CellView::Closed,
+                CellView::Mine,
+            ]
+        );
+    }
+
+    #[test]
+    fn spectator_debug() {
+        let mut ms = board!["*..", "..*"];
+        ms.open((0, 1)).unwrap();
+        let debug = format!("{:?}", SpectatorView::new(&ms));
+        assert!(debug.contains("Number(2)"));
+        assert!(!debug.contains("Mine"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialize() {
+        let ms = board!["*.", ".."];
+        let json = serde_json::to_string(&BoardView::new(&ms)).unwrap();
+        assert_eq!(
+            json,
+            r#"{"height":2,"width":2,"flagged":0,"opened":0,"mines_left":1,"cells":["closed","closed","closed","closed"]}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<BoardView>(&json).unwrap(),
+            BoardView::new(&ms)
+        );
+    }
+}
+
+mod save {
+    use crate::{Difficulty, Error, MSHash, MSMatrix, MineSweeper, Move, SavedGame};
+
+    #[test]
+    fn restore() {
+        let mut save = SavedGame::new(Difficulty::medium(), 42);
+        save.moves = vec![
+            Move::ToggleFlag((15, 15)),
+            Move::Open((3, 4)),
+            Move::ToggleFlag((15, 15)),
+        ];
+        let ms: MSMatrix = save.restore().unwrap();
+        assert_eq!(ms.started_from(), Some((3, 4)));
+        assert_eq!(
+            ms,
+            <MSMatrix as MineSweeper>::from_seed(Difficulty::medium(), (3, 4), 42)
+                .map(|mut ms| {
+                    ms.open((3, 4)).unwrap();
+                    ms
+                })
+                .unwrap()
+        );
+        assert_eq!(
+            ms.cells_snapshot(),
+            save.restore::<MSHash>().unwrap().cells_snapshot()
+        );
+
+        let redacted = save.redacted();
+        assert_eq!(redacted.moves, save.moves);
+        assert!(matches!(
+            redacted.restore::<MSMatrix>(),
+            Err(Error::MissingSeed)
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialize() {
+        let mut save = SavedGame::new(Difficulty::easy(), 7);
+        save.moves.push(Move::Open((1, 2)));
+        let json = serde_json::to_string(&save.redacted()).unwrap();
+        assert_eq!(
+            json,
+            r#"{"difficulty":{"height":9,"width":9,"mines":10},"moves":[{"Open":[1,2]}]}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<SavedGame>(&json).unwrap(),
+            save.redacted()
+        );
+    }
+}
+
+mod validate {
+    use crate::{
+        validate_moves, CellContent, Difficulty, Error, MSConst, MSMatrix, MineSweeper, Move,
+        Rejection, TimedMove, Verdict,
+    };
+
+    #[test]
+    fn verdicts() {
+        let difficulty = Difficulty::easy();
+        let ms = <MSMatrix as MineSweeper>::from_seed(difficulty, (4, 4), 5).unwrap();
+        let coords = (0..9).flat_map(|r| (0..9).map(move |c| (r, c)));
+        let is_mine = |coord| ms.get_cell(coord).unwrap().content == CellContent::Mine;
+        let timed = |time, action| TimedMove { time, action };
+
+        let mut moves = vec![timed(0, Move::Open((4, 4)))];
+        assert_eq!(
+            validate_moves::<MSMatrix>(5, difficulty, &moves),
+            Verdict::Unfinished
+        );
+        moves.extend(
+            coords
+                .clone()
+                .filter(|&coord| !is_mine(coord))
+                .map(|coord| timed(100, Move::Open(coord))),
+        );
+        assert_eq!(
+            validate_moves::<MSMatrix>(5, difficulty, &moves),
+            Verdict::Won { time: 100 }
+        );
+        // the board is the same on all the implementations
+        assert_eq!(
+            validate_moves::<MSConst<9, 9>>(5, difficulty, &moves),
+            Verdict::Won { time: 100 }
+        );
+        // the same moves on another board open a mine sooner or later
+        assert!(matches!(
+            validate_moves::<MSMatrix>(6, difficulty, &moves),
+            Verdict::Lost | Verdict::Rejected { .. }
+        ));
+
+        moves.push(timed(100, Move::ToggleFlag((0, 0))));
+        let last = moves.len() - 1;
+        assert_eq!(
+            validate_moves::<MSMatrix>(5, difficulty, &moves),
+            Verdict::Rejected {
+                index: last,
+                reason: Rejection::GameOver
+            }
+        );
+
+        let mine = coords.clone().find(|&coord| is_mine(coord)).unwrap();
+        let moves = [timed(0, Move::Open((4, 4))), timed(10, Move::Open(mine))];
+        assert_eq!(
+            validate_moves::<MSMatrix>(5, difficulty, &moves),
+            Verdict::Lost
+        );
+
+        let moves = [timed(10, Move::Open((4, 4))), timed(5, Move::Open((0, 0)))];
+        assert_eq!(
+            validate_moves::<MSMatrix>(5, difficulty, &moves),
+            Verdict::Rejected {
+                index: 1,
+                reason: Rejection::TimeWentBackwards
+            }
+        );
+
+        let moves = [
+            timed(0, Move::Open((4, 4))),
+            timed(0, Move::ToggleFlag((4, 4))),
+        ];
+        assert_eq!(
+            validate_moves::<MSMatrix>(5, difficulty, &moves),
+            Verdict::Rejected {
+                index: 1,
+                reason: Rejection::Illegal(Error::AlreadyOpen)
+            }
+        );
+    }
+}
+
+#[cfg(feature = "tokio")]
+mod async_game {
+    use std::sync::{Arc, Mutex};
+
+    use crate::{
+        board, AsyncGame, AsyncMineSweeper, CellState, Coordinate, Error, Game, MineSweeper,
+        MineSweeperExt, Move, MoveEvent,
+    };
+
+    #[tokio::test]
+    async fn shared() {
+        let game = AsyncGame::new(board!["*..", "..."]);
+        let mut events = game.subscribe();
+        let other = game.clone();
+        tokio::spawn(async move { other.open((0, 1)).await.unwrap() })
+            .await
+            .unwrap();
+        game.toggle_flag((0, 0)).await.unwrap();
+        assert!(game.play(Move::ToggleFlag((0, 1))).await.is_err());
+
+        assert_eq!(game.moves().await, 2);
+        assert_eq!(game.state().await.mines_left, 0);
+        assert_eq!(game.lock().await.board().get_game_state().opened, 1);
+        let MoveEvent { action, moves, .. } = events.recv().await.unwrap();
+        assert_eq!((action, moves), (Move::Open((0, 1)), 1));
+        assert_eq!(
+            events.recv().await.unwrap().action,
+            Move::ToggleFlag((0, 0))
+        );
+        assert!(events.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn rules_and_hooks() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let hook_seen = Arc::clone(&seen);
+        let game = Game::builder(board!["*..", "..."])
+            .chord(false)
+            .auto_finish(true)
+            .on_move(move |_, action, _| hook_seen.lock().unwrap().push(action))
+            .build();
+        let game = AsyncGame::from_game(game);
+        let mut events = game.subscribe();
+
+        game.open((1, 2)).await.unwrap();
+        assert_eq!(game.open((1, 2)).await, Err(Error::AlreadyOpen));
+        game.open((1, 0)).await.unwrap();
+        assert_eq!(game.moves().await, 3);
+        assert!(game.lock().await.board().is_cleared());
+        let expected = [
+            Move::Open((1, 2)),
+            Move::Open((1, 0)),
+            Move::ToggleFlag((0, 0)),
+        ];
+        assert_eq!(*seen.lock().unwrap(), expected);
+        for action in expected {
+            assert_eq!(events.recv().await.unwrap().action, action);
+        }
+    }
+
+    async fn open_all(ms: &impl AsyncMineSweeper, coords: &[Coordinate]) -> usize {
+        let mut opened = 0;
+        for &coord in coords {
+            opened += ms.open(coord).await.map_or(0, |result| result.cells_opened);
+        }
+        opened
+    }
+
+    #[tokio::test]
+    async fn async_trait() {
+        let game = AsyncGame::new(board!["*..", "..."]);
+        assert_eq!(open_all(&game, &[(0, 1), (9, 9), (1, 0)]).await, 2);
+        AsyncMineSweeper::toggle_flag(&game, (0, 0)).await.unwrap();
+        assert_eq!(
+            AsyncMineSweeper::get_cell(&game, (0, 0))
+                .await
+                .unwrap()
+                .state,
+            CellState::Flagged
+        );
+        assert_eq!(game.get_game_state().await.opened, 2);
+    }
+}
+
+mod storage {
+    use std::{fs, io};
+
+    use crate::{Difficulty, FileStorage, Move, SavedGame, Storage};
+
+    #[test]
+    fn file_storage() {
+        let dir = std::env::temp_dir().join(format!("mine_sweeperr_{}", std::process::id()));
+        let mut storage = FileStorage::new(&dir).unwrap();
+        let mut game = SavedGame::new(Difficulty::hard(), u64::MAX);
+        game.moves = vec![Move::ToggleFlag((0, 29)), Move::Open((15, 0))];
+
+        assert_eq!(storage.load("game-1").unwrap(), None);
+        storage.save("game-1", &game).unwrap();
+        storage.save("game_2", &game.redacted()).unwrap();
+        assert_eq!(storage.load("game-1").unwrap(), Some(game.clone()));
+        assert_eq!(storage.load("game_2").unwrap(), Some(game.redacted()));
+        let mut ids = storage.list().unwrap();
+        ids.sort();
+        assert_eq!(ids, ["game-1", "game_2"]);
+
+        storage.delete("game-1").unwrap();
+        storage.delete("game-1").unwrap();
+        assert_eq!(storage.list().unwrap(), ["game_2"]);
+        assert_eq!(
+            storage.save("../game", &game).unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+        fs::write(dir.join("game_2.msave"), "9 9").unwrap();
+        assert_eq!(
+            storage.load("game_2").unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
+
+mod simulate {
+    use crate::{
+        simulate::{Comparison, RandomStrategy, Simulation, Strategy},
+        solver::{CSPSolver, NonDeterministic},
+        Difficulty, GameRules, MSHash, MSMatrix, Move, SpectatorView,
+    };
+
+    /// Flags a corner before playing randomly.
+    struct FlagFirst(bool, RandomStrategy);
+
+    impl Strategy<MSMatrix> for FlagFirst {
+        fn next_move(&mut self, view: SpectatorView<'_, MSMatrix>) -> Option<Move> {
+            if self.0 {
+                self.1.next_move(view)
+            } else {
+                self.0 = true;
+                Some(Move::ToggleFlag((0, 0)))
+            }
+        }
+    }
+
+    #[test]
+    fn random_games() {
+        let simulation = Simulation::new(Difficulty::medium(), 50);
+        let statistics = simulation.run::<MSMatrix, _>(RandomStrategy::new);
+        assert_eq!(statistics.games, 50);
+        // the first click is always safe
+        assert_eq!(statistics.first_click_loss_rate(), 0.0);
+        assert_eq!(statistics.won + statistics.lost, 50);
+        assert!(statistics.average_cells_opened() >= 9.0);
+        // each game starts with a guess
+        assert!(statistics.guesses >= Some(50));
+        // the games only depend on the seed
+        assert_eq!(
+            simulation.run::<MSMatrix, _>(RandomStrategy::new),
+            statistics
+        );
+        assert_eq!(
+            simulation.run::<MSHash, _>(RandomStrategy::new).games,
+            statistics.games
+        );
+        // also when the first move is not an open
+        let flag_first = |seed| FlagFirst(false, RandomStrategy::new(seed));
+        let statistics = simulation.run::<MSMatrix, _>(flag_first);
+        assert_eq!(simulation.run::<MSMatrix, _>(flag_first), statistics);
+        assert_eq!(statistics.first_click_losses, 0);
+        assert_eq!(statistics.guesses, None);
+        // more lives mean more opened cells
+        let lives = Simulation {
+            rules: GameRules {
+                lives: 3,
+                ..GameRules::default()
+            },
+            ..simulation
+        };
+        assert!(
+            lives.run::<MSMatrix, _>(RandomStrategy::new).cells_opened > statistics.cells_opened
+        );
+    }
+
+    #[test]
+    fn report() {
+        let simulation = Simulation {
+            seed: 3,
+            ..Simulation::new(Difficulty::easy(), 30)
+        };
+        let mut comparison = Comparison::<MSMatrix>::new(simulation);
+        let boards = comparison.boards().to_vec();
+        comparison
+            .add_solver::<CSPSolver>("csp")
+            .add_strategy("random", RandomStrategy::new)
+            .add_solver::<NonDeterministic>("all");
+        // the entries don't change the boards
+        assert_eq!(comparison.boards(), &boards[..]);
+        let report = comparison.report();
+        assert_eq!(report.entries.len(), 3);
+        assert!(report.entries[0].won > report.entries[1].won);
+        assert_eq!(report.entries[2].won, 30);
+        // the deterministic solver never guesses, the random strategy guesses at each move
+        assert_eq!(report.entries[0].guesses, Some(0));
+        assert!(report.entries[0].guesses < report.entries[1].guesses);
+        assert!(report.entries[1].guesses_per_board >= Some(1.0));
+        assert_eq!(report.entries[2].guesses, None);
+        // the strategies are measured on the same boards every time
+        let mut again = Comparison::<MSMatrix>::new(simulation);
+        again.add_strategy("random", RandomStrategy::new);
+        assert_eq!(again.report().entries[0].won, report.entries[1].won);
+        assert!(report
+            .to_string()
+            .lines()
+            .nth(2)
+            .unwrap()
+            .starts_with("csp"));
+        #[cfg(feature = "serde")]
+        assert_eq!(
+            &serde_json::from_str::<crate::simulate::Report>(
+                &serde_json::to_string(report).unwrap()
+            )
+            .unwrap(),
+            report
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn parallel() {
+        let simulation = Simulation::new(Difficulty::easy(), 200);
+        assert_eq!(
+            simulation.run_parallel::<MSMatrix, _>(RandomStrategy::new),
+            simulation.run::<MSMatrix, _>(RandomStrategy::new)
+        );
+    }
+}
+
+mod env {
+    use crate::{
+        env::{Environment, Rewards},
+        CellContent, CellState, Difficulty, MSMatrix, MineSweeper, Move,
+    };
+
+    #[test]
+    fn episodes() {
+        let rewards = Rewards::default();
+        let mut env = Environment::<MSMatrix>::new(Difficulty::easy(), 7);
+        assert_eq!(env.reset().opened, 0);
+        let (observation, reward, done) = env.step(Move::Open((4, 4)));
+        assert!(!done);
+        assert_eq!(reward, rewards.cell * observation.opened as f64);
+        // opening the same cell again has no effect
+        let (_, reward, _) = env.step(Move::Open((4, 4)));
+        assert_eq!(reward, rewards.invalid);
+        let board = env.board().clone();
+        let cells = board.cells_snapshot();
+        let mine = cells
+            .iter()
+            .position(|cell| cell.content == CellContent::Mine)
+            .unwrap();
+        let (_, reward, done) = env.step(Move::Open((mine / 9, mine % 9)));
+        assert!(done);
+        assert_eq!(reward, rewards.loss);
+        assert_eq!(env.step(Move::Open((0, 0))).1, rewards.invalid);
+
+        // a new board, won by opening all the safe cells
+        env.reset();
+        env.step(Move::Open((4, 4)));
+        assert_ne!(env.board().cells_snapshot(), cells);
+        let mut total = 0.0;
+        for (i, cell) in env.board().cells_snapshot().into_iter().enumerate() {
+            let closed = env.board().get_cell((i / 9, i % 9)).unwrap().state == CellState::Closed;
+            if cell.content != CellContent::Mine && closed {
+                let (_, reward, _) = env.step(Move::Open((i / 9, i % 9)));
+                total += reward;
+            }
+        }
+        assert!(env.is_done());
+        assert!(total > rewards.win);
+        assert_eq!(env.step(Move::ToggleFlag((0, 0))).1, rewards.invalid);
+
+        // the same seed gives the same episodes
+        let mut other = Environment::<MSMatrix>::new(Difficulty::easy(), 7);
+        other.reset();
+        other.step(Move::Open((4, 4)));
+        assert_eq!(other.board(), &board);
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn step_all() {
+        let mut envs: Vec<_> = (0..8)
+            .map(|seed| Environment::<MSMatrix>::new(Difficulty::easy(), seed))
+            .collect();
+        let mut sequential = envs.clone();
+        let actions = vec![Move::Open((4, 4)); 8];
+        let results = crate::env::step_all(&mut envs, &actions);
+        for ((env, result), other) in envs.iter().zip(results).zip(&mut sequential) {
+            assert_eq!(other.step(Move::Open((4, 4))), result);
+            assert_eq!(other.board(), env.board());
+        }
+    }
+}
+
+#[cfg(feature = "ndarray")]
+mod tensor {
+    use crate::{
+        tensor::{self, CHANNELS, CLOSED, FLAGGED},
+        BoardView, CellState, Difficulty, MSMatrix, MineSweeper, Move,
+    };
+
+    #[test]
+    fn observation() {
+        let mut ms = <MSMatrix as MineSweeper>::from_seed(Difficulty::easy(), (4, 4), 5).unwrap();
+        ms.open((4, 4)).unwrap();
+        let closed = ms
+            .cells_snapshot()
+            .iter()
+            .position(|cell| cell.state == CellState::Closed)
+            .unwrap();
+        ms.toggle_flag((closed / 9, closed % 9)).unwrap();
+        let view = BoardView::new(&ms);
+        let observation = tensor::observation(&view);
+        assert_eq!(observation.shape(), &[CHANNELS, 9, 9]);
+        // each cell that is closed or shows a number has exactly one of those channels set
+        for r in 0..9 {
+            for c in 0..9 {
+                let set = (0..=CLOSED)
+                    .filter(|&ch| observation[(ch, r, c)] == 1.0)
+                    .count();
+                assert_eq!(set, 1);
+            }
+        }
+        assert_eq!(
+            observation.sum_axis(ndarray::Axis(0)).sum(),
+            81.0 + view.flagged as f32
+        );
+        assert_eq!(
+            observation.index_axis(ndarray::Axis(0), FLAGGED).sum(),
+            view.flagged as f32
+        );
+    }
+
+    #[test]
+    fn actions() {
+        assert_eq!(tensor::action_count(3, 4), 24);
+        for index in 0..24 {
+            let action = tensor::action(index, 3, 4).unwrap();
+            assert_eq!(tensor::action_index(action, 3, 4), index);
+        }
+        assert_eq!(tensor::action(5, 3, 4), Some(Move::Open((1, 1))));
+        assert_eq!(tensor::action(17, 3, 4), Some(Move::ToggleFlag((1, 1))));
+        assert_eq!(tensor::action(24, 3, 4), None);
+    }
+}
+
+mod puzzle {
+    use crate::{puzzle::Puzzle, CellView, Difficulty};
+
+    #[test]
+    fn generate() {
+        for seed in 0..5 {
+            let puzzle = Puzzle::generate(Difficulty::easy(), seed).unwrap();
+            assert_eq!(puzzle, Puzzle::generate(Difficulty::easy(), seed).unwrap());
+            let (r, c) = puzzle.safe;
+            assert_eq!(puzzle.view.cells[r * 9 + c], CellView::Closed);
+            assert!(puzzle.is_solution(puzzle.safe));
+            let text = puzzle.to_string();
+            assert_eq!(text.lines().count(), 9);
+            assert_eq!(text.lines().nth(r).unwrap().chars().nth(c), Some('C'));
+            #[cfg(feature = "serde")]
+            assert_eq!(
+                serde_json::from_str::<Puzzle>(&serde_json::to_string(&puzzle).unwrap()).unwrap(),
+                puzzle
+            );
+        }
+    }
+}
+
+mod analysis {
+    use crate::{
+        analysis::{components, density_heatmap, symmetries, ComponentKind, Symmetry},
+        board, BoardView, MineSweeper,
+    };
+
+    #[test]
+    fn symmetries_of_boards() {
+        assert_eq!(
+            symmetries(&board!["*..", "...", "..*"]),
+            vec![
+                Symmetry::Diagonal,
+                Symmetry::AntiDiagonal,
+                Symmetry::Rotate180
+            ]
+        );
+        assert_eq!(symmetries(&board!["*.*", "...", "*.*"]), Symmetry::ALL);
+        assert_eq!(
+            symmetries(&board!["*..*", "...."]),
+            vec![Symmetry::Vertical]
+        );
+        assert_eq!(symmetries(&board!["*..", "..*", "..."]), vec![]);
+        // each rotation is undone by the opposite one
+        for (r, c) in [(0, 0), (0, 2), (1, 2), (2, 1)] {
+            let rotated = Symmetry::Rotate90.apply((r, c), 3, 3);
+            assert_eq!(Symmetry::Rotate270.apply(rotated, 3, 3), (r, c));
+            assert_eq!(
+                Symmetry::Rotate90.apply(rotated, 3, 3),
+                Symmetry::Rotate180.apply((r, c), 3, 3)
+            );
+        }
+    }
+
+    #[test]
+    fn components_of_views() {
+        // 0 0 1 C C
+        // 0 0 1 C C
+        // 1 1 1 0 0
+        // C C C C C
+        // C C C C C
+        let mut ms = board![
+            ". . . * ."
+            ". . . . ."
+            ". . . . ."
+            ". * . . ."
+            ". . . . *"
+        ];
+        ms.open((0, 0)).unwrap();
+        ms.open_one((2, 3)).unwrap();
+        ms.open_one((2, 4)).unwrap();
+        let components = components(&BoardView::new(&ms));
+        let kinds: Vec<_> = components.iter().map(|component| component.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ComponentKind::Opening,
+                ComponentKind::Border,
+                ComponentKind::Closed,
+                ComponentKind::Opening,
+                ComponentKind::Closed,
+            ]
+        );
+        assert_eq!(components[0].cells, vec![(0, 0), (0, 1), (1, 0), (1, 1)]);
+        assert_eq!(
+            components[1].cells,
+            vec![(0, 2), (1, 2), (2, 0), (2, 1), (2, 2)]
+        );
+        assert_eq!(components[2].cells, vec![(0, 3), (0, 4), (1, 3), (1, 4)]);
+        assert_eq!(components[3].cells, vec![(2, 3), (2, 4)]);
+        assert_eq!(components[4].cells.len(), 10);
+    }
+
+    #[test]
+    fn density() {
+        let ms = board!["*..*", "*...", "...."];
+        assert_eq!(
+            density_heatmap(&ms, 2),
+            vec![vec![0.5, 0.0, 0.25], vec![0.25, 0.0, 0.0]]
+        );
+        assert_eq!(density_heatmap(&ms, 1)[0], vec![1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(density_heatmap(&ms, 3), vec![vec![2.0 / 9.0, 1.0 / 9.0]]);
+        assert!(density_heatmap(&ms, 0).is_empty());
+        assert!(density_heatmap(&ms, 4).is_empty());
+    }
+}
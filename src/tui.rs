@@ -0,0 +1,176 @@
+//! A terminal frontend built on [`ratatui`] and [`crossterm`](ratatui::crossterm).
+//!
+//! [`BoardWidget`] draws any [`MineSweeper`] and [`BoardState`] turns key and mouse events
+//! into [moves](Move) that can be [played](crate::Game::play):
+//! - arrows or `h`, `j`, `k`, `l` move the cursor
+//! - space, enter or a left click open a cell, or chord if it is already open
+//! - `f` or a right click toggle a flag
+
+use ratatui::{
+    buffer::Buffer,
+    crossterm::event::{Event, KeyCode, KeyEventKind, MouseButton, MouseEventKind},
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    widgets::StatefulWidget,
+};
+
+use crate::{
+    frontend::InputTranslator, Cell, CellContent, CellState, Coordinate, MineSweeper, Move,
+};
+
+/// Draws a board, two terminal columns per cell.
+#[derive(Debug)]
+pub struct BoardWidget<'a, M: MineSweeper> {
+    ms: &'a M,
+}
+
+impl<'a, M: MineSweeper> BoardWidget<'a, M> {
+    pub fn new(ms: &'a M) -> Self {
+        BoardWidget { ms }
+    }
+}
+
+impl<M: MineSweeper> StatefulWidget for BoardWidget<'_, M> {
+    type State = BoardState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut BoardState) {
+        state.area = area;
+        let width = self.ms.width();
+        for (i, cell) in self.ms.cells_snapshot().into_iter().enumerate() {
+            let coord @ (r, c) = (i / width, i % width);
+            let (x, y) = (c * 2, r);
+            if x + 1 >= area.width as usize || y >= area.height as usize {
+                continue;
+            }
+            let mut style = style(cell);
+            if coord == state.cursor {
+                style = style.add_modifier(Modifier::REVERSED);
+            }
+            buf.set_string(
+                area.x + x as u16,
+                area.y + y as u16,
+                format!("{cell} "),
+                style,
+            );
+        }
+    }
+}
+
+fn style(cell: Cell) -> Style {
+    let color = match (cell.state, cell.content) {
+        (CellState::Closed, _) => Color::DarkGray,
+        (CellState::Flagged, _) => Color::Yellow,
+        (CellState::Open, CellContent::Mine) => Color::Red,
+        (CellState::Open, CellContent::Number(1)) => Color::Blue,
+        (CellState::Open, CellContent::Number(2)) => Color::Green,
+        (CellState::Open, _) => Color::Magenta,
+    };
+    Style::default().fg(color)
+}
+
+/// The cursor of a [`BoardWidget`] and the area where it was last drawn.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BoardState {
+    pub cursor: Coordinate,
+    area: Rect,
+}
+
+impl BoardState {
+    /// Handles an event, moving the cursor if needed,
+    /// and returns the move requested by the player, if any.
+    pub fn handle_event(&mut self, ms: &impl MineSweeper, event: &Event) -> Option<Move> {
+        match event {
+            Event::Key(key) if key.kind != KeyEventKind::Release => {
+                let (r, c) = self.cursor;
+                match key.code {
+                    KeyCode::Up | KeyCode::Char('k') => self.cursor.0 = r.saturating_sub(1),
+                    KeyCode::Down | KeyCode::Char('j') => {
+                        self.cursor.0 = (r + 1).min(ms.height() - 1)
+                    }
+                    KeyCode::Left | KeyCode::Char('h') => self.cursor.1 = c.saturating_sub(1),
+                    KeyCode::Right | KeyCode::Char('l') => {
+                        self.cursor.1 = (c + 1).min(ms.width() - 1)
+                    }
+                    KeyCode::Char(' ') | KeyCode::Enter => return Some(Move::Open(self.cursor)),
+                    KeyCode::Char('f') => return Some(Move::ToggleFlag(self.cursor)),
+                    _ => {}
+                }
+                None
+            }
+            Event::Mouse(mouse) => {
+                let MouseEventKind::Down(button) = mouse.kind else {
+                    return None;
+                };
+                self.cursor = self.to_coordinate(ms, (mouse.column as f32, mouse.row as f32))?;
+                match button {
+                    MouseButton::Left => Some(Move::Open(self.cursor)),
+                    MouseButton::Right => Some(Move::ToggleFlag(self.cursor)),
+                    MouseButton::Middle => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+impl InputTranslator for BoardState {
+    fn cell_size(&self) -> (f32, f32) {
+        (2.0, 1.0)
+    }
+
+    fn origin(&self) -> (f32, f32) {
+        (self.area.x as f32, self.area.y as f32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{
+        buffer::Buffer,
+        crossterm::event::{
+            Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+        },
+        layout::Rect,
+        widgets::StatefulWidget,
+    };
+
+    use super::{BoardState, BoardWidget};
+    use crate::{board, MineSweeper, Move};
+
+    #[test]
+    fn render_and_events() {
+        let mut ms = board!["*..", "..."];
+        ms.open((0, 1)).unwrap();
+        let mut state = BoardState::default();
+        let mut buf = Buffer::empty(Rect::new(0, 0, 10, 3));
+        BoardWidget::new(&ms).render(Rect::new(2, 1, 8, 2), &mut buf, &mut state);
+        assert_eq!(buf[(2, 1)].symbol(), "C");
+        assert_eq!(buf[(4, 1)].symbol(), "1");
+
+        let key = |code| Event::Key(KeyEvent::new(code, KeyModifiers::NONE));
+        assert_eq!(state.handle_event(&ms, &key(KeyCode::Down)), None);
+        assert_eq!(state.handle_event(&ms, &key(KeyCode::Down)), None);
+        assert_eq!(
+            state.handle_event(&ms, &key(KeyCode::Char('f'))),
+            Some(Move::ToggleFlag((1, 0)))
+        );
+
+        let click = |button, column, row| {
+            Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Down(button),
+                column,
+                row,
+                modifiers: KeyModifiers::NONE,
+            })
+        };
+        assert_eq!(
+            state.handle_event(&ms, &click(MouseButton::Left, 7, 2)),
+            Some(Move::Open((1, 2)))
+        );
+        assert_eq!(state.cursor, (1, 2));
+        assert_eq!(
+            state.handle_event(&ms, &click(MouseButton::Left, 1, 2)),
+            None
+        );
+    }
+}
@@ -1,59 +1,208 @@
-use std::fmt::Write;
+use alloc::{
+    collections::VecDeque,
+    string::{String, ToString},
+};
+use core::fmt::Write;
 
-use crate::{CellContent, CellState, Coordinate, Error::OutOfBounds, MineSweeper, Result};
+use crate::{Cell, CellContent, CellState, Coordinate, Error, MineSweeper, OpenResult, Result};
 
 /// Contains emoji numbers from 0 to 9. position 10 is the emoji to represent a 0-cell.
 pub(crate) const NUMBERS: [&str; 11] = ["0️⃣", "1️⃣", "2️⃣", "3️⃣", "4️⃣", "5️⃣", "6️⃣", "7️⃣", "8️⃣", "9️⃣", "🟩"];
 pub(crate) const ROW_NUMBER_RIGHT_SEPARATOR: &str = "  ";
 
+/// A collection of at most 8 neighboring coordinates, returned by [`get_neighboring_closed`].
+/// With the `smallvec` feature enabled it is a [`SmallVec`](smallvec::SmallVec) allocated on the stack,
+/// otherwise it is a [`Vec`](alloc::vec::Vec).
+#[cfg(feature = "smallvec")]
+pub type Neighbors = smallvec::SmallVec<[Coordinate; 8]>;
+/// A collection of at most 8 neighboring coordinates, returned by [`get_neighboring_closed`].
+/// With the `smallvec` feature enabled it is a `SmallVec` allocated on the stack,
+/// otherwise it is a [`Vec`](alloc::vec::Vec).
+#[cfg(not(feature = "smallvec"))]
+pub type Neighbors = alloc::vec::Vec<Coordinate>;
+
+/// Returns an iterator over the neighbors of the given cell (excluded the cell itself).
+/// If the coordinates are out of bounds the iterator yields nothing.
+pub fn neighbors(
+    coord @ (r, c): Coordinate,
+    height: usize,
+    width: usize,
+) -> impl Iterator<Item = Coordinate> {
+    let in_bounds = r < height && c < width;
+    (r.saturating_sub(1)..=r.saturating_add(1).min(height.saturating_sub(1)))
+        .take_while(move |_| in_bounds)
+        .flat_map(move |i| {
+            (c.saturating_sub(1)..=c.saturating_add(1).min(width.saturating_sub(1)))
+                .map(move |j| (i, j))
+        })
+        .filter(move |&pos| pos != coord)
+}
+
 /// Returns an iterator over the neighbors of the given cell (excluded the cell itself).
-/// If the coordinates are out of bounds returns [`OutOfBounds`](OutOfBounds).
-/// You can safely unwrap the result if you are sure that the given coordinates are in bounds.
+/// If the coordinates are out of bounds returns [`OutOfBounds`](Error::OutOfBounds).
+/// Use [`neighbors`] if you are sure that the given coordinates are in bounds.
 pub fn iter_neighbors(
     coord @ (r, c): Coordinate,
     height: usize,
     width: usize,
 ) -> Result<impl Iterator<Item = Coordinate>> {
     if r >= height || c >= width {
-        Err(OutOfBounds)
+        Err(Error::OutOfBounds {
+            coord,
+            height,
+            width,
+        })
     } else {
-        Ok((r.saturating_sub(1)..=(r + 1).min(height - 1))
-            .flat_map(move |i| (c.saturating_sub(1)..=(c + 1).min(width - 1)).map(move |j| (i, j)))
-            .filter(move |&pos| pos != coord))
+        Ok(neighbors(coord, height, width))
+    }
+}
+
+/// Opens a cell following all the additional rules suggested in the [trait interface](MineSweeper::open),
+/// so that custom implementors don't need to write the opening procedure themselves.
+///
+/// The state of the cells is read using [`get_cell`](MineSweeper::get_cell),
+/// while `open_cell` is called once for each closed cell that has to be opened
+/// and must only mark the given cell as open.
+/// The returned [`OpenResult`] tells how many cells have been opened and how many mines exploded,
+/// so that the implementor can update its counters.
+/// The neighbors of a number are opened when [`opens_neighbors`](MineSweeper::opens_neighbors) says so.
+///
+/// The opening procedure is made using a [queue](VecDeque) (not recursive).
+/// If the coordinates are out of bounds returns [`OutOfBounds`](Error::OutOfBounds).
+pub fn flood_open<M: MineSweeper>(
+    ms: &mut M,
+    start: Coordinate,
+    mut open_cell: impl FnMut(&mut M, Coordinate),
+) -> Result<OpenResult> {
+    ms.get_cell(start)?;
+    let (height, width) = (ms.height(), ms.width());
+    let (mut cells_opened, mut mines_exploded) = (0, 0);
+    let mut queue = VecDeque::from([start]);
+    while let Some(coord) = queue.pop_front() {
+        let cell = ms.get_cell(coord).unwrap();
+        match cell.state {
+            CellState::Flagged => continue,
+            CellState::Closed => {
+                open_cell(ms, coord);
+                cells_opened += 1;
+                if cell.content == CellContent::Mine {
+                    mines_exploded += 1;
+                }
+            }
+            // a cell can be queued more than once before being opened,
+            // but its neighbors must only be expanded the first time
+            CellState::Open if coord != start => continue,
+            CellState::Open => {}
+        }
+        if let CellContent::Number(number) = cell.content {
+            if ms.opens_neighbors(number, count_neighboring_flags(ms, coord)) {
+                queue.extend(
+                    neighbors(coord, height, width).filter(|&neighbor| {
+                        ms.get_cell(neighbor).unwrap().state != CellState::Open
+                    }),
+                );
+            }
+        }
     }
+    Ok(OpenResult::new(
+        ms.get_cell(start).unwrap(),
+        cells_opened,
+        mines_exploded,
+    ))
 }
 
-pub(crate) fn get_neighboring_flags(
+/// Returns the key of a cell used to compute the [`position_hash`](MineSweeper::position_hash) of a board.
+///
+/// The key depends on the coordinate of the cell, on its state and, if open, on its content.
+/// Closed cells don't contribute to the hash, so their key is `0`.
+/// A custom implementation can keep the hash of its position updated
+/// by xor-ing the key of a cell before and after each change.
+pub fn zobrist_key((r, c): Coordinate, cell: Cell) -> u64 {
+    let tag = match (cell.state, cell.content) {
+        (CellState::Closed, _) => return 0,
+        (CellState::Flagged, _) => 0,
+        (CellState::Open, CellContent::Number(n)) => 1 + n as u64,
+        (CellState::Open, CellContent::Mine) => 10,
+    };
+    // The finalizer of SplitMix64, so that keys don't need to be stored in a table.
+    let mut z = ((r as u64) << 36 ^ (c as u64) << 4 ^ tag).wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Computes the [`position_hash`](MineSweeper::position_hash) of a board from scratch.
+pub(crate) fn compute_position_hash(ms: &impl MineSweeper) -> u64 {
+    let width = ms.width();
+    ms.cells_snapshot()
+        .into_iter()
+        .enumerate()
+        .fold(0, |hash, (index, cell)| {
+            hash ^ zobrist_key((index / width, index % width), cell)
+        })
+}
+
+/// Returns `true` if the given coordinate is the starting point or one of its neighbors.
+/// Runs in constant time, so it can be called for each attempt when placing mines.
+pub(crate) fn is_in_safe_zone((r, c): Coordinate, (start_r, start_c): Coordinate) -> bool {
+    r.abs_diff(start_r) <= 1 && c.abs_diff(start_c) <= 1
+}
+
+/// Returns an iterator over the flagged neighbors of the given cell.
+/// If the coordinates are out of bounds the iterator yields nothing.
+pub fn get_neighboring_flags(
     ms: &impl MineSweeper,
     coord: Coordinate,
 ) -> impl Iterator<Item = Coordinate> + '_ {
-    iter_neighbors(coord, ms.height(), ms.width())
-        .unwrap()
-        .filter(|&neighbor| ms.get_cell(neighbor).unwrap().state == CellState::Flagged)
+    get_neighboring_in_state(ms, coord, CellState::Flagged)
 }
 
-pub(crate) fn count_neighboring_flags(ms: &impl MineSweeper, coord: Coordinate) -> u8 {
+/// Counts the flagged neighbors of the given cell.
+/// Flags are counted whether they are correct or not.
+pub fn count_neighboring_flags(ms: &impl MineSweeper, coord: Coordinate) -> u8 {
     get_neighboring_flags(ms, coord).count() as u8
 }
 
-pub(crate) fn get_neighboring_mines(
+/// Returns the closed (neither open nor flagged) neighbors of the given cell.
+/// If the coordinates are out of bounds the result is empty.
+///
+/// Unlike the other helpers this returns a collection, since solvers usually go through
+/// the closed neighbors more than once. With the `smallvec` feature it doesn't allocate.
+pub fn get_neighboring_closed(ms: &impl MineSweeper, coord: Coordinate) -> Neighbors {
+    get_neighboring_in_state(ms, coord, CellState::Closed).collect()
+}
+
+/// Counts the closed (neither open nor flagged) neighbors of the given cell.
+pub fn count_neighboring_closed(ms: &impl MineSweeper, coord: Coordinate) -> u8 {
+    get_neighboring_in_state(ms, coord, CellState::Closed).count() as u8
+}
+
+/// Returns an iterator over the neighbors of the given cell that contain a mine.
+/// If the coordinates are out of bounds the iterator yields nothing.
+///
+/// This reads the content of closed cells too, so it is meant for implementors
+/// and not for solvers, which should only rely on open cells.
+pub fn get_neighboring_mines(
     ms: &impl MineSweeper,
     coord: Coordinate,
 ) -> impl Iterator<Item = Coordinate> + '_ {
-    iter_neighbors(coord, ms.height(), ms.width())
-        .unwrap()
+    neighbors(coord, ms.height(), ms.width())
         .filter(|&neighbor| ms.get_cell(neighbor).unwrap().content == CellContent::Mine)
 }
 
-pub(crate) fn count_neighboring_mines(ms: &impl MineSweeper, coord: Coordinate) -> u8 {
+/// Counts the neighbors of the given cell that contain a mine.
+/// Like [`get_neighboring_mines`], this reads the content of closed cells too.
+pub fn count_neighboring_mines(ms: &impl MineSweeper, coord: Coordinate) -> u8 {
     get_neighboring_mines(ms, coord).count() as u8
 }
 
-pub(crate) fn get_neighboring_closed(ms: &impl MineSweeper, coord: Coordinate) -> Vec<Coordinate> {
-    iter_neighbors(coord, ms.height(), ms.width())
-        .unwrap()
-        .filter(|&neighbor| ms.get_cell(neighbor).unwrap().state == CellState::Closed)
-        .collect()
+fn get_neighboring_in_state(
+    ms: &impl MineSweeper,
+    coord: Coordinate,
+    state: CellState,
+) -> impl Iterator<Item = Coordinate> + '_ {
+    neighbors(coord, ms.height(), ms.width())
+        .filter(move |&neighbor| ms.get_cell(neighbor).unwrap().state == state)
 }
 
 /// Returns a string representing the superior numbers indicating columns, to be read in vertical.
@@ -0,0 +1,80 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{save::place_mines_before, Difficulty, Error, MineSweeper, MineSweeperExt, Move};
+
+/// A move with the time it was made, in milliseconds since the start of the game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TimedMove {
+    pub time: u64,
+    pub action: Move,
+}
+
+/// The reason why a sequence of moves was rejected by [`validate_moves`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rejection {
+    /// The move was made before the previous one.
+    TimeWentBackwards,
+    /// The move was made after the game was won or lost.
+    GameOver,
+    /// The move is not allowed, like flagging an open cell or opening a cell out of bounds.
+    Illegal(Error),
+}
+
+/// The outcome of [`validate_moves`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// All the safe cells have been opened, at the time of the last move.
+    Won { time: u64 },
+    /// A mine has been opened.
+    Lost,
+    /// The moves are valid but the game is not over.
+    Unfinished,
+    /// The move at the given index is not valid.
+    Rejected { index: usize, reason: Rejection },
+}
+
+/// Replays a sequence of moves on a board of type `M` generated with the given seed
+/// and checks that it is a valid game.
+///
+/// The board is the same [restored](crate::SavedGame::restore) as `M` from a save with the same seed,
+/// so servers can check the games submitted by clients without sending them the mines.
+pub fn validate_moves<M: MineSweeper>(
+    seed: u64,
+    difficulty: Difficulty,
+    moves: &[TimedMove],
+) -> Verdict {
+    let mut ms = match M::new_unstarted(difficulty) {
+        Ok(ms) => ms,
+        Err(error) => {
+            return Verdict::Rejected {
+                index: 0,
+                reason: Rejection::Illegal(error),
+            }
+        }
+    };
+    let mut verdict = Verdict::Unfinished;
+    let mut last_time = 0;
+    for (index, &TimedMove { time, action }) in moves.iter().enumerate() {
+        let reject = |reason| Verdict::Rejected { index, reason };
+        if time < last_time {
+            return reject(Rejection::TimeWentBackwards);
+        }
+        if verdict != Verdict::Unfinished {
+            return reject(Rejection::GameOver);
+        }
+        last_time = time;
+        let exploded = place_mines_before(&mut ms, seed, action).and_then(|()| match action {
+            Move::Open(coord) => Ok(ms.open(coord)?.mines_exploded > 0),
+            Move::ToggleFlag(coord) => ms.toggle_flag(coord).map(|_| false),
+        });
+        verdict = match exploded {
+            Ok(true) => Verdict::Lost,
+            Ok(false) if ms.is_cleared() => Verdict::Won { time },
+            Ok(false) => Verdict::Unfinished,
+            Err(error) => return reject(Rejection::Illegal(error)),
+        };
+    }
+    verdict
+}
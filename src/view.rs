@@ -0,0 +1,132 @@
+use alloc::vec::Vec;
+use core::fmt::{Debug, Formatter};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Cell, CellContent, CellState, Coordinate, GameState, MineSweeper, Result};
+
+/// What a player can see of a [`cell`](Cell): the content of closed and flagged cells is hidden.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum CellView {
+    Closed,
+    Flagged,
+    Number(u8),
+    Mine,
+}
+
+impl From<Cell> for CellView {
+    fn from(cell: Cell) -> Self {
+        match (cell.state, cell.content) {
+            (CellState::Closed, _) => CellView::Closed,
+            (CellState::Flagged, _) => CellView::Flagged,
+            (CellState::Open, CellContent::Number(n)) => CellView::Number(n),
+            (CellState::Open, CellContent::Mine) => CellView::Mine,
+        }
+    }
+}
+
+/// What a player can see of a board, safe to send to clients.
+///
+/// With the `serde` feature this can be serialized, without ever leaking the position
+/// of the mines that are still closed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BoardView {
+    pub height: usize,
+    pub width: usize,
+    pub flagged: usize,
+    pub opened: usize,
+    pub mines_left: usize,
+    /// The [visible cells](CellView), in row-major order.
+    pub cells: Vec<CellView>,
+}
+
+impl BoardView {
+    /// Takes a snapshot of what a player can see of the given board.
+    pub fn new(ms: &impl MineSweeper) -> Self {
+        let state = ms.get_game_state();
+        BoardView {
+            height: ms.height(),
+            width: ms.width(),
+            flagged: state.flagged,
+            opened: state.opened,
+            mines_left: state.mines_left,
+            cells: ms
+                .cells_snapshot()
+                .into_iter()
+                .map(CellView::from)
+                .collect(),
+        }
+    }
+}
+
+/// A read-only handle to a board, exposing only what a player can see.
+///
+/// Spectators can be given this instead of the board, so they can neither change the game
+/// nor read the position of the closed mines.
+/// Use [`Game::watch`](crate::Game::watch) to receive one after each move.
+pub struct SpectatorView<'a, M: MineSweeper> {
+    ms: &'a M,
+}
+
+/// Only formats the [`BoardView`] of the board, so that debugging output doesn't show the closed mines either.
+impl<M: MineSweeper> Debug for SpectatorView<'_, M> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SpectatorView")
+            .field("board", &self.board_view())
+            .finish()
+    }
+}
+
+impl<M: MineSweeper> Clone for SpectatorView<'_, M> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<M: MineSweeper> Copy for SpectatorView<'_, M> {}
+
+impl<'a, M: MineSweeper> SpectatorView<'a, M> {
+    pub fn new(ms: &'a M) -> Self {
+        SpectatorView { ms }
+    }
+
+    /// Returns what can be seen of a cell.
+    /// If the coordinates are out of bounds returns [`OutOfBounds`](crate::Error::OutOfBounds).
+    pub fn get_cell(&self, coord: Coordinate) -> Result<CellView> {
+        self.ms.get_cell(coord).map(CellView::from)
+    }
+
+    pub fn height(&self) -> usize {
+        self.ms.height()
+    }
+
+    pub fn width(&self) -> usize {
+        self.ms.width()
+    }
+
+    pub fn mines(&self) -> usize {
+        self.ms.mines()
+    }
+
+    pub fn get_game_state(&self) -> GameState {
+        self.ms.get_game_state()
+    }
+
+    /// Returns the [position hash](MineSweeper::position_hash) of the board,
+    /// which only depends on the visible cells.
+    pub fn position_hash(&self) -> u64 {
+        self.ms.position_hash()
+    }
+
+    /// Takes a snapshot of the whole board.
+    pub fn board_view(&self) -> BoardView {
+        BoardView::new(self.ms)
+    }
+}
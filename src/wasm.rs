@@ -0,0 +1,63 @@
+//! Bindings to use the crate from JavaScript.
+
+use wasm_bindgen::prelude::*;
+
+use crate::{solver::NonDeterministic, Cell, Difficulty, MSMatrix, MineSweeper};
+
+/// A game on a [`MSMatrix`](MSMatrix), exported to JavaScript.
+#[wasm_bindgen]
+pub struct Board(MSMatrix);
+
+#[wasm_bindgen]
+impl Board {
+    /// Creates a new game with the given parameters, starting from `(row, column)`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        height: usize,
+        width: usize,
+        mines: usize,
+        row: usize,
+        column: usize,
+    ) -> Result<Board, JsValue> {
+        MSMatrix::new::<NonDeterministic>(Difficulty::custom(height, width, mines), (row, column))
+            .map(Board)
+            .map_err(to_js)
+    }
+
+    /// Opens the cell at `(row, column)` and returns the number of cells opened.
+    pub fn open(&mut self, row: usize, column: usize) -> Result<usize, JsValue> {
+        self.0
+            .open((row, column))
+            .map(|result| result.cells_opened)
+            .map_err(to_js)
+    }
+
+    /// Toggles the flag on the cell at `(row, column)`.
+    pub fn toggle_flag(&mut self, row: usize, column: usize) -> Result<(), JsValue> {
+        self.0.toggle_flag((row, column)).map(drop).map_err(to_js)
+    }
+
+    pub fn height(&self) -> usize {
+        self.0.height()
+    }
+
+    pub fn width(&self) -> usize {
+        self.0.width()
+    }
+
+    /// Returns the whole board as a `Uint8Array` in row-major order,
+    /// with one byte per cell [packed](Cell::to_byte).
+    ///
+    /// Frontends can copy it once per frame instead of asking for each cell.
+    pub fn board_as_u8_array(&self) -> Vec<u8> {
+        self.0
+            .cells_snapshot()
+            .into_iter()
+            .map(Cell::to_byte)
+            .collect()
+    }
+}
+
+fn to_js(error: crate::Error) -> JsValue {
+    JsValue::from_str(&error.to_string())
+}